@@ -67,3 +67,62 @@ fn serde_json_large(b: &mut Bencher) {
     serde_json::from_str::<serde_json::Value>(LARGE_JSON_STR).unwrap();
   });
 }
+
+/// About 4 MB of objects like `LARGE_JSON_STR`.
+fn multi_mb_json() -> String {
+  format!("[{}]", vec![LARGE_JSON_STR; 2000].join(",\n"))
+}
+
+#[bench]
+fn json_parser_multi_mb(b: &mut Bencher) {
+  let input = multi_mb_json();
+  b.bytes = input.len() as u64;
+  b.iter(|| {
+    parse_json(&input).unwrap();
+  });
+}
+
+#[bench]
+fn structural_multi_mb(b: &mut Bencher) {
+  let input = multi_mb_json();
+  b.bytes = input.len() as u64;
+  b.iter(|| {
+    structural::parse(&input).unwrap();
+  });
+}
+
+#[bench]
+fn structural_index_multi_mb(b: &mut Bencher) {
+  let input = multi_mb_json();
+  b.bytes = input.len() as u64;
+  b.iter(|| {
+    structural::StructuralIndex::new(&input);
+  });
+}
+
+#[bench]
+fn structural_index_scalar_multi_mb(b: &mut Bencher) {
+  let input = multi_mb_json();
+  b.bytes = input.len() as u64;
+  b.iter(|| {
+    structural::StructuralIndex::with_implementation(&input, structural::Implementation::Scalar);
+  });
+}
+
+#[bench]
+fn serde_json_multi_mb(b: &mut Bencher) {
+  let input = multi_mb_json();
+  b.bytes = input.len() as u64;
+  b.iter(|| {
+    serde_json::from_str::<serde_json::Value>(&input).unwrap();
+  });
+}
+
+#[bench]
+fn parallel_multi_mb(b: &mut Bencher) {
+  let input = multi_mb_json();
+  b.bytes = input.len() as u64;
+  b.iter(|| {
+    parallel::parse_parallel(&input, &parallel::ParallelOptions::default()).unwrap();
+  });
+}
@@ -0,0 +1,36 @@
+#![feature(test)]
+extern crate test;
+use test::Bencher;
+
+extern crate json_parser;
+use json_parser::*;
+use json_parser::ndjson::*;
+
+/// About 8 MB of JSON Lines.
+fn ndjson() -> String {
+  (0..50000)
+    .map(|i| {
+      format!(r#"{{"id": {}, "name": "user {}", "email": "user{}@example.com", "active": {}, "score": {}.5, "tags": ["a", "b", "c"], "address": {{"street": "{} Main St", "city": "Springfield"}}}}"#,
+              i, i, i, i % 2 == 0, i % 100, i)
+    })
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+#[bench]
+fn sequential_ndjson(b: &mut Bencher) {
+  let input = ndjson();
+  b.bytes = input.len() as u64;
+  b.iter(|| {
+    input.lines().map(|line| parse_json(line).unwrap()).count()
+  });
+}
+
+#[bench]
+fn parallel_ndjson(b: &mut Bencher) {
+  let input = ndjson();
+  b.bytes = input.len() as u64;
+  b.iter(|| {
+    parse_parallel(&input, &NdjsonOptions::default()).len()
+  });
+}
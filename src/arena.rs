@@ -0,0 +1,168 @@
+//! Parsing into a bump arena.
+//!
+//! All the containers and unescaped strings of a document are allocated in
+//! a caller-provided `Bump`, and freed all at once when it is dropped or
+//! reset. Strings without escapes borrow from the input.
+
+use std::borrow::Cow;
+use std::str;
+use bumpalo::collections::Vec as BumpVec;
+use JsonValue;
+use parser::JsonParser;
+use parse_error::ParseResult;
+
+pub use bumpalo::Bump;
+
+/// A JSON value whose contents live in an arena or in the input.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum ArenaValue<'a> {
+    String(&'a str),
+    Number(f64),
+    Boolean(bool),
+    /// Members in document order. If a key appears more than once, the last
+    /// member wins, as in `JsonValue::Object`.
+    Object(&'a [(&'a str, ArenaValue<'a>)]),
+    Array(&'a [ArenaValue<'a>]),
+    Null,
+}
+
+impl<'a> ArenaValue<'a> {
+    /// Look up the member `key` of an object.
+    pub fn get(&self, key: &str) -> Option<&'a ArenaValue<'a>> {
+        match *self {
+            ArenaValue::Object(members) => {
+                members.iter().rev().find(|&&(k, _)| k == key).map(|&(_, ref value)| value)
+            }
+            _ => None,
+        }
+    }
+
+    /// Copy this value into a `JsonValue`, borrowing its strings.
+    pub fn to_json_value(&self) -> JsonValue<'a> {
+        match *self {
+            ArenaValue::String(s) => JsonValue::String(Cow::Borrowed(s)),
+            ArenaValue::Number(n) => JsonValue::Number(n),
+            ArenaValue::Boolean(b) => JsonValue::Boolean(b),
+            ArenaValue::Object(members) => {
+                JsonValue::Object(members.iter()
+                                         .map(|&(key, ref value)| {
+                                             (Cow::Borrowed(key), value.to_json_value())
+                                         })
+                                         .collect())
+            }
+            ArenaValue::Array(elements) => {
+                JsonValue::Array(elements.iter().map(|value| value.to_json_value()).collect())
+            }
+            ArenaValue::Null => JsonValue::Null,
+        }
+    }
+}
+
+/// Parse `input`, allocating in `arena`.
+pub fn parse_in<'a>(input: &'a str, arena: &'a Bump) -> ParseResult<ArenaValue<'a>> {
+    let parser = ArenaParser {
+        parser: JsonParser::new(input),
+        arena: arena,
+    };
+
+    parser.parse_value()
+}
+
+struct ArenaParser<'a> {
+    parser: JsonParser<'a>,
+    arena: &'a Bump,
+}
+
+impl<'a> ArenaParser<'a> {
+    fn parse_value(&self) -> ParseResult<ArenaValue<'a>> {
+        Ok(match self.parser.current_byte() {
+            Some(b'"') => ArenaValue::String(try!(self.parse_string())),
+            Some(b'{') => ArenaValue::Object(try!(self.parse_object())),
+            Some(b'[') => ArenaValue::Array(try!(self.parse_array())),
+            Some(b'0'...b'9') | Some(b'-') | Some(b't') | Some(b'f') | Some(b'n') => {
+                match try!(self.parser.parse_scalar()) {
+                    JsonValue::Number(n) => ArenaValue::Number(n),
+                    JsonValue::Boolean(b) => ArenaValue::Boolean(b),
+                    _ => ArenaValue::Null,
+                }
+            }
+            Some(_) => {
+                return Err(self.parser.unexpected_character(vec!['"', '{', '[', '-', 't', 'f', 'n']))
+            }
+            None => return Err(self.parser.unexpected_end_of_input()),
+        })
+    }
+
+    fn parse_string(&self) -> ParseResult<&'a str> {
+        let mut buf = BumpVec::new_in(self.arena);
+
+        Ok(match try!(self.parser.parse_string_into(&mut buf)) {
+            Some(string) => string,
+            // See `JsonParser::parse_string`.
+            None => unsafe { str::from_utf8_unchecked(buf.into_bump_slice()) },
+        })
+    }
+
+    fn parse_object(&self) -> ParseResult<&'a [(&'a str, ArenaValue<'a>)]> {
+        let mut members = BumpVec::new_in(self.arena);
+
+        try!(self.parse_container('{', '}', || {
+            let key = try!(self.parse_string());
+            self.parser.expect_optional_whitespace();
+            try!(self.parser.expect(':'));
+            self.parser.expect_optional_whitespace();
+            members.push((key, try!(self.parse_value())));
+            Ok(())
+        }));
+
+        Ok(members.into_bump_slice())
+    }
+
+    fn parse_array(&self) -> ParseResult<&'a [ArenaValue<'a>]> {
+        let mut elements = BumpVec::new_in(self.arena);
+
+        try!(self.parse_container('[', ']', || {
+            elements.push(try!(self.parse_value()));
+            Ok(())
+        }));
+
+        Ok(elements.into_bump_slice())
+    }
+
+    /// Parse the brackets and separators of a container, calling `item` at
+    /// the start of each member or element.
+    fn parse_container<F>(&self, open: char, close: char, mut item: F) -> ParseResult<()>
+        where F: FnMut() -> ParseResult<()>
+    {
+        try!(self.parser.enter());
+        try!(self.parser.expect(open));
+        self.parser.expect_optional_whitespace();
+
+        if self.parser.current_byte() == Some(close as u8) {
+            self.parser.leave();
+            return self.parser.expect(close);
+        }
+
+        loop {
+            try!(item());
+            self.parser.expect_optional_whitespace();
+
+            match self.parser.current_byte() {
+                Some(b',') => {
+                    try!(self.parser.expect(','));
+                    self.parser.expect_optional_whitespace();
+
+                    if self.parser.current_byte() == Some(close as u8) {
+                        break;
+                    }
+                }
+                Some(b) if b == close as u8 => break,
+                Some(_) => return Err(self.parser.unexpected_character(vec![',', close])),
+                None => return Err(self.parser.unexpected_end_of_input()),
+            }
+        }
+
+        self.parser.leave();
+        self.parser.expect(close)
+    }
+}
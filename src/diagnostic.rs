@@ -0,0 +1,177 @@
+//! Rendering parse errors as snippets of the input, in the style of compiler
+//! diagnostics:
+//!
+//! ```text
+//! error: expected ',' or '}', found '"'
+//!  --> line 3, column 10
+//!   |
+//! 1 | {
+//! 2 |   "a": 1,
+//! 3 |   "b": 2 "c": 3
+//!   |          ^ expected ',' or '}'
+//! 4 | }
+//!   |
+//!   = help: a ',' may be missing before this value
+//! ```
+
+use std::fmt;
+use parse_error::{ParseError, ParseErrorKind};
+
+#[derive(Clone, Debug)]
+pub struct RenderOptions {
+    /// The number of lines shown before and after the line of the error.
+    pub context_lines: usize,
+    /// Highlight the output with ANSI escape codes.
+    pub color: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> RenderOptions {
+        RenderOptions {
+            context_lines: 2,
+            color: false,
+        }
+    }
+}
+
+/// A rendered error, see `render`.
+pub struct Diagnostic<'a> {
+    input: &'a str,
+    error: &'a ParseError,
+    options: RenderOptions,
+}
+
+/// Render `error`, which happened while parsing `input`. The result
+/// implements `Display`.
+pub fn render<'a>(input: &'a str, error: &'a ParseError, options: RenderOptions) -> Diagnostic<'a> {
+    Diagnostic {
+        input: input,
+        error: error,
+        options: options,
+    }
+}
+
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+impl<'a> Diagnostic<'a> {
+    /// Write `text` in `color`, if colors are enabled.
+    fn paint(&self, f: &mut fmt::Formatter, color: &str, text: &str) -> fmt::Result {
+        if self.options.color {
+            write!(f, "{}{}{}", color, text, RESET)
+        } else {
+            f.write_str(text)
+        }
+    }
+
+    /// Write the gutter of a line, with its number if any.
+    fn gutter(&self, f: &mut fmt::Formatter, width: usize, number: Option<usize>) -> fmt::Result {
+        let number = number.map_or(String::new(), |number| number.to_string());
+        self.paint(f, BLUE, &format!("{:>1$} |", number, width))
+    }
+}
+
+impl<'a> fmt::Display for Diagnostic<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let error = self.error;
+        let lines: Vec<&str> = self.input.split('\n').map(|line| line.trim_end_matches('\r')).collect();
+        let line = error.line() - 1;
+        let first = line.saturating_sub(self.options.context_lines);
+        let last = (line + self.options.context_lines).min(lines.len() - 1);
+        let width = (last + 1).to_string().len();
+
+        try!(self.paint(f, RED, "error"));
+
+        let message = match *error.kind() {
+            ParseErrorKind::UnexpectedCharacter(found, _) => {
+                format!(": {}, found {:?}", error.kind(), found)
+            }
+            ref kind => format!(": {}", kind),
+        };
+
+        try!(self.paint(f, BOLD, &message));
+        try!(f.write_str("\n"));
+        try!(write!(f, "{:1$}", "", width));
+        try!(self.paint(f, BLUE, "-->"));
+        try!(f.write_str(" "));
+
+        match error.pointer() {
+            Some(pointer) if !pointer.is_empty() => try!(write!(f, "{}, ", pointer)),
+            _ => {}
+        }
+
+        try!(write!(f, "line {}, column {}\n", error.line(), error.column()));
+        try!(self.gutter(f, width, None));
+        try!(f.write_str("\n"));
+
+        for (i, text) in lines.iter().enumerate().take(last + 1).skip(first) {
+            try!(self.gutter(f, width, Some(i + 1)));
+            try!(write!(f, " {}\n", text));
+
+            if i == line {
+                // Keep the tabs before the caret, so that it lines up.
+                let padding: String = text.chars()
+                                          .take(error.column() - 1)
+                                          .map(|c| if c == '\t' { '\t' } else { ' ' })
+                                          .collect();
+
+                try!(self.gutter(f, width, None));
+                try!(write!(f, " {}", padding));
+                try!(self.paint(f, RED, &format!("^ {}", error.kind())));
+                try!(f.write_str("\n"));
+            }
+        }
+
+        try!(self.gutter(f, width, None));
+        try!(f.write_str("\n"));
+
+        if let Some(hint) = hint(self.input, error) {
+            try!(write!(f, "{:1$} ", "", width));
+            try!(self.paint(f, BOLD, "= help:"));
+            try!(write!(f, " {}\n", hint));
+        }
+
+        Ok(())
+    }
+}
+
+/// Recognize common mistakes, such as JavaScript or Python syntax.
+fn hint(input: &str, error: &ParseError) -> Option<&'static str> {
+    let (found, expected) = match *error.kind() {
+        ParseErrorKind::UnexpectedCharacter(found, ref expected) => (found, expected),
+        _ => return None,
+    };
+
+    let rest = input.get(error.offset()..).unwrap_or("");
+    let expects_value = expected.contains(&'[');
+
+    if found == '\'' {
+        return Some("strings must be enclosed in double quotes");
+    }
+
+    if expects_value {
+        let literals = [("True", "use `true` instead of `True`"),
+                        ("False", "use `false` instead of `False`"),
+                        ("None", "use `null` instead of `None`")];
+
+        for &(literal, hint) in &literals {
+            if rest.starts_with(literal) {
+                return Some(hint);
+            }
+        }
+    }
+
+    if *expected == ['"'] && (found.is_alphabetic() || found == '_') {
+        return Some("object keys must be strings in double quotes");
+    }
+
+    if expected.len() == 2 && expected[0] == ',' &&
+       (found == '"' || found == '{' || found == '[' || found == '-' || found.is_ascii_digit() ||
+        rest.starts_with("true") || rest.starts_with("false") || rest.starts_with("null")) {
+        return Some("a ',' may be missing before this value");
+    }
+
+    None
+}
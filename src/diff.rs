@@ -0,0 +1,268 @@
+use std::mem;
+use JsonValue;
+use patch::PatchOperation;
+use pointer::escape_token;
+
+/// A difference between two `JsonValue`s, located by a JSON Pointer relative
+/// to the root of the old value.
+#[derive(PartialEq, Debug)]
+pub enum Change<'v, 'a: 'v> {
+    Added { path: String, value: &'v JsonValue<'a> },
+    Removed { path: String, value: &'v JsonValue<'a> },
+    /// A scalar was changed to another value of the same type.
+    Changed {
+        path: String,
+        old: &'v JsonValue<'a>,
+        new: &'v JsonValue<'a>,
+    },
+    /// A value was replaced by a value of another type.
+    TypeChanged {
+        path: String,
+        old: &'v JsonValue<'a>,
+        new: &'v JsonValue<'a>,
+    },
+}
+
+/// How array elements are matched between the old and the new value.
+#[derive(PartialEq, Clone, Debug)]
+pub enum ArrayMode {
+    /// Elements are compared index by index.
+    Ordered,
+    /// Arrays are compared as multisets: only elements which appear in one
+    /// array but not in the other are reported, and added elements are
+    /// located at `-` (the end of the array).
+    Unordered,
+    /// Object elements are matched by the value of the given member and
+    /// diffed recursively; other elements are compared as in `Unordered`.
+    ById(String),
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct DiffOptions {
+    pub arrays: ArrayMode,
+}
+
+impl Default for DiffOptions {
+    fn default() -> DiffOptions {
+        DiffOptions { arrays: ArrayMode::Ordered }
+    }
+}
+
+/// Compare `old` and `new`, comparing arrays index by index.
+pub fn diff<'v, 'a>(old: &'v JsonValue<'a>, new: &'v JsonValue<'a>) -> Vec<Change<'v, 'a>> {
+    diff_with_options(old, new, &DiffOptions::default())
+}
+
+/// Compare `old` and `new`.
+///
+/// Object members are visited in key order, so the output is deterministic.
+/// `Raw` values are compared by content, and parsed if their text differs,
+/// see `RawValue::value`.
+pub fn diff_with_options<'v, 'a>(old: &'v JsonValue<'a>,
+                                 new: &'v JsonValue<'a>,
+                                 options: &DiffOptions)
+                                 -> Vec<Change<'v, 'a>> {
+    let mut differ = Differ {
+        options: options,
+        changes: Vec::new(),
+    };
+
+    differ.diff_values(String::new(), old, new);
+    differ.changes
+}
+
+/// Convert a list of changes into an equivalent JSON Patch.
+pub fn to_patch<'v, 'a>(changes: &[Change<'v, 'a>]) -> Vec<PatchOperation<'a>> {
+    changes.iter()
+           .map(|change| {
+               match *change {
+                   Change::Added { ref path, value } => {
+                       PatchOperation::Add {
+                           path: path.clone(),
+                           value: value.clone(),
+                       }
+                   }
+                   Change::Removed { ref path, .. } => PatchOperation::Remove { path: path.clone() },
+                   Change::Changed { ref path, new, .. } |
+                   Change::TypeChanged { ref path, new, .. } => {
+                       PatchOperation::Replace {
+                           path: path.clone(),
+                           value: new.clone(),
+                       }
+                   }
+               }
+           })
+           .collect()
+}
+
+struct Differ<'o, 'v, 'a: 'v> {
+    options: &'o DiffOptions,
+    changes: Vec<Change<'v, 'a>>,
+}
+
+fn child_path(path: &str, token: &str) -> String {
+    format!("{}/{}", path, escape_token(token))
+}
+
+impl<'o, 'v, 'a> Differ<'o, 'v, 'a> {
+    fn diff_values(&mut self, path: String, old: &'v JsonValue<'a>, new: &'v JsonValue<'a>) {
+        // Raw values are compared by content, unless they have the same text.
+        if let (&JsonValue::Raw(ref old), &JsonValue::Raw(ref new)) = (old, new) {
+            if old.get() == new.get() {
+                return;
+            }
+        }
+
+        let (old, new) = (old.resolved(), new.resolved());
+
+        match (old, new) {
+            (&JsonValue::Object(ref old_members), &JsonValue::Object(ref new_members)) => {
+                let mut keys: Vec<_> = old_members.keys().chain(new_members.keys()).collect();
+                keys.sort();
+                keys.dedup();
+
+                for key in keys {
+                    let path = child_path(&path, key);
+
+                    match (old_members.get(key), new_members.get(key)) {
+                        (Some(old), Some(new)) => self.diff_values(path, old, new),
+                        (Some(old), None) => {
+                            self.changes.push(Change::Removed {
+                                path: path,
+                                value: old,
+                            })
+                        }
+                        (None, Some(new)) => {
+                            self.changes.push(Change::Added {
+                                path: path,
+                                value: new,
+                            })
+                        }
+                        (None, None) => unreachable!(),
+                    }
+                }
+            }
+            (&JsonValue::Array(ref old_elements), &JsonValue::Array(ref new_elements)) => {
+                match self.options.arrays {
+                    ArrayMode::Ordered => self.diff_ordered(&path, old_elements, new_elements),
+                    ArrayMode::Unordered => {
+                        let old_elements: Vec<_> = old_elements.iter().enumerate().collect();
+                        let new_elements: Vec<_> = new_elements.iter().collect();
+                        self.diff_unordered(&path, old_elements, new_elements);
+                    }
+                    ArrayMode::ById(ref id) => {
+                        self.diff_by_id(&path, id, old_elements, new_elements)
+                    }
+                }
+            }
+            _ => {
+                if old == new {
+                    return;
+                }
+
+                if mem::discriminant(old) == mem::discriminant(new) {
+                    self.changes.push(Change::Changed {
+                        path: path,
+                        old: old,
+                        new: new,
+                    });
+                } else {
+                    self.changes.push(Change::TypeChanged {
+                        path: path,
+                        old: old,
+                        new: new,
+                    });
+                }
+            }
+        }
+    }
+
+    fn diff_ordered(&mut self,
+                    path: &str,
+                    old_elements: &'v [JsonValue<'a>],
+                    new_elements: &'v [JsonValue<'a>]) {
+        for (idx, (old, new)) in old_elements.iter().zip(new_elements).enumerate() {
+            self.diff_values(child_path(path, &idx.to_string()), old, new);
+        }
+
+        // Removals go from the end so that each path is still valid when
+        // the changes are applied in order.
+        for idx in (new_elements.len()..old_elements.len()).rev() {
+            self.changes.push(Change::Removed {
+                path: child_path(path, &idx.to_string()),
+                value: &old_elements[idx],
+            });
+        }
+
+        for (idx, new) in new_elements.iter().enumerate().skip(old_elements.len()) {
+            self.changes.push(Change::Added {
+                path: child_path(path, &idx.to_string()),
+                value: new,
+            });
+        }
+    }
+
+    /// Report the elements which can't be paired with an equal element.
+    /// `old_elements` keep their index in the old array.
+    fn diff_unordered(&mut self,
+                      path: &str,
+                      old_elements: Vec<(usize, &'v JsonValue<'a>)>,
+                      mut new_elements: Vec<&'v JsonValue<'a>>) {
+        let mut removed = Vec::new();
+
+        for (idx, old) in old_elements {
+            match new_elements.iter().position(|new| *new == old) {
+                Some(position) => {
+                    new_elements.remove(position);
+                }
+                None => removed.push((idx, old)),
+            }
+        }
+
+        for (idx, old) in removed.into_iter().rev() {
+            self.changes.push(Change::Removed {
+                path: child_path(path, &idx.to_string()),
+                value: old,
+            });
+        }
+
+        for new in new_elements {
+            self.changes.push(Change::Added {
+                path: child_path(path, "-"),
+                value: new,
+            });
+        }
+    }
+
+    fn diff_by_id(&mut self,
+                  path: &str,
+                  id: &str,
+                  old_elements: &'v [JsonValue<'a>],
+                  new_elements: &'v [JsonValue<'a>]) {
+        let id_of = |value: &'v JsonValue<'a>| {
+            match *value.resolved() {
+                JsonValue::Object(ref members) => members.get(id),
+                _ => None,
+            }
+        };
+
+        let mut unmatched_old = Vec::new();
+        let mut unmatched_new: Vec<_> = new_elements.iter().collect();
+
+        for (idx, old) in old_elements.iter().enumerate() {
+            let matching = id_of(old).and_then(|old_id| {
+                unmatched_new.iter().position(|new| id_of(new) == Some(old_id))
+            });
+
+            match matching {
+                Some(position) => {
+                    let new = unmatched_new.remove(position);
+                    self.diff_values(child_path(path, &idx.to_string()), old, new);
+                }
+                None => unmatched_old.push((idx, old)),
+            }
+        }
+
+        self.diff_unordered(path, unmatched_old, unmatched_new);
+    }
+}
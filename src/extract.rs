@@ -0,0 +1,216 @@
+use JsonValue;
+use parser::JsonParser;
+use parse_error::{ParseError, ParseResult};
+use pointer::{PointerError, parse_pointer};
+
+#[derive(Debug)]
+pub enum ExtractError {
+    /// The pointer at this index is invalid.
+    InvalidPointer(usize, PointerError),
+    Parse(ParseError),
+}
+
+/// The reference tokens of all the requested pointers, merged into a tree.
+#[derive(Default)]
+struct Target {
+    /// Indices of the pointers which designate this value.
+    pointers: Vec<usize>,
+    children: Vec<Child>,
+}
+
+struct Child {
+    token: String,
+    /// The token as an array index, if it is one.
+    index: Option<usize>,
+    target: Target,
+}
+
+impl Target {
+    fn child_mut(&mut self, token: &str) -> &mut Target {
+        match self.children.iter().position(|child| child.token == token) {
+            Some(position) => &mut self.children[position].target,
+            None => {
+                let canonical = !token.is_empty() && token.bytes().all(|b| b.is_ascii_digit()) &&
+                                !(token.len() > 1 && token.starts_with('0'));
+
+                self.children.push(Child {
+                    token: token.to_string(),
+                    index: if canonical { token.parse().ok() } else { None },
+                    target: Target::default(),
+                });
+
+                &mut self.children.last_mut().unwrap().target
+            }
+        }
+    }
+}
+
+struct Extractor<'input> {
+    parser: JsonParser<'input>,
+    results: Vec<Option<JsonValue<'input>>>,
+    remaining: usize,
+}
+
+/// Parse only the values of `input` designated by `pointers`.
+///
+/// Subtrees which don't contain any of the requested values are validated
+/// but not built. Scanning stops as soon as every value has been found, so
+/// the rest of the input isn't validated at all. Objects are still scanned
+/// to their end, since like with `parse_json`, the last of duplicate keys
+/// wins.
+///
+/// The values are returned in the same order as `pointers`, or `None` if
+/// they don't exist.
+pub fn extract<'input>(input: &'input str,
+                       pointers: &[&str])
+                       -> Result<Vec<Option<JsonValue<'input>>>, ExtractError> {
+    let mut root = Target::default();
+
+    for (idx, pointer) in pointers.iter().enumerate() {
+        let tokens = try!(parse_pointer(pointer).map_err(|e| ExtractError::InvalidPointer(idx, e)));
+        let mut target = &mut root;
+
+        for token in tokens {
+            target = target.child_mut(&token);
+        }
+
+        target.pointers.push(idx);
+    }
+
+    let mut extractor = Extractor {
+        parser: JsonParser::new(input),
+        results: vec![None; pointers.len()],
+        remaining: pointers.len(),
+    };
+
+    if extractor.remaining > 0 {
+        extractor.parser.expect_optional_whitespace();
+        try!(extractor.visit(&root).map_err(ExtractError::Parse));
+    }
+
+    Ok(extractor.results)
+}
+
+impl<'input> Extractor<'input> {
+    /// Store `value` as the result of the pointers of `target` and of its
+    /// descendants.
+    fn fill(&mut self, target: &Target, value: &JsonValue<'input>) {
+        for &idx in &target.pointers {
+            if self.results[idx].is_none() {
+                self.results[idx] = Some(value.clone());
+                self.remaining -= 1;
+            }
+        }
+
+        for child in &target.children {
+            let child_value = match *value {
+                JsonValue::Object(ref members) => members.get(&*child.token),
+                JsonValue::Array(ref elements) => child.index.and_then(|idx| elements.get(idx)),
+                _ => None,
+            };
+
+            if let Some(child_value) = child_value {
+                self.fill(&child.target, child_value);
+            }
+        }
+    }
+
+    /// Forget the results of the pointers of `target` and of its
+    /// descendants, when a duplicate key replaces them.
+    fn clear(&mut self, target: &Target) {
+        for &idx in &target.pointers {
+            if self.results[idx].take().is_some() {
+                self.remaining += 1;
+            }
+        }
+
+        for child in &target.children {
+            self.clear(&child.target);
+        }
+    }
+
+    /// Returns `true` once every pointer has been resolved.
+    fn visit(&mut self, target: &Target) -> ParseResult<bool> {
+        if !target.pointers.is_empty() {
+            // The pointer of an error would be relative to the target.
+            let value = try!(self.parser.parse_value().map_err(|mut error| {
+                error.set_pointer(None);
+                error
+            }));
+            self.fill(target, &value);
+
+            return Ok(self.remaining == 0);
+        }
+
+        match self.parser.current_byte() {
+            Some(b'{') => self.visit_container(target, '{', '}'),
+            Some(b'[') => self.visit_container(target, '[', ']'),
+            _ => self.parser.skip_value().map(|_| false),
+        }
+    }
+
+    fn visit_container(&mut self, target: &Target, open: char, close: char) -> ParseResult<bool> {
+        try!(self.parser.enter());
+        let result = self.visit_container_content(target, open, close);
+        self.parser.leave();
+        result
+    }
+
+    fn visit_container_content(&mut self, target: &Target, open: char, close: char) -> ParseResult<bool> {
+        try!(self.parser.expect(open));
+        self.parser.expect_optional_whitespace();
+
+        if self.parser.current_byte() == Some(close as u8) {
+            return self.parser.expect(close).map(|_| self.remaining == 0);
+        }
+
+        let mut idx = 0;
+
+        loop {
+            let child = if open == '{' {
+                let key = try!(self.parser.parse_string());
+                self.parser.expect_optional_whitespace();
+                try!(self.parser.expect(':'));
+                self.parser.expect_optional_whitespace();
+
+                target.children.iter().find(|child| child.token == key)
+            } else {
+                target.children.iter().find(|child| child.index == Some(idx))
+            };
+
+            match child {
+                // A later duplicate key could still replace the values found
+                // in an object, so only arrays are left early.
+                Some(child) if open == '{' => {
+                    self.clear(&child.target);
+                    try!(self.visit(&child.target));
+                }
+                Some(child) => {
+                    if try!(self.visit(&child.target)) {
+                        return Ok(true);
+                    }
+                }
+                None => try!(self.parser.skip_value()),
+            }
+
+            self.parser.expect_optional_whitespace();
+            idx += 1;
+
+            match self.parser.current_byte() {
+                Some(b',') => {
+                    try!(self.parser.expect(','));
+                    self.parser.expect_optional_whitespace();
+
+                    if self.parser.current_byte() == Some(close as u8) {
+                        return self.parser.expect(close).map(|_| self.remaining == 0);
+                    }
+                }
+                Some(b) if b == close as u8 => {
+                    return self.parser.expect(close).map(|_| self.remaining == 0)
+                }
+                Some(_) => return Err(self.parser.unexpected_character(vec![',', close])),
+                None => return Err(self.parser.unexpected_end_of_input()),
+            }
+        }
+    }
+}
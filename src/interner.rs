@@ -0,0 +1,116 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// An interned string, valid for the `Interner` which created it.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug)]
+pub struct Symbol(u32);
+
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+pub struct InternerStats {
+    /// Number of distinct strings.
+    pub symbols: usize,
+    /// Number of calls to `intern`, including the ones made by parsers.
+    pub lookups: usize,
+    /// Number of lookups which found an existing symbol.
+    pub hits: usize,
+}
+
+impl InternerStats {
+    /// The proportion of lookups which found an existing symbol, or 0 if
+    /// there were none.
+    pub fn hit_rate(&self) -> f64 {
+        if self.lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / self.lookups as f64
+        }
+    }
+}
+
+/// A set of strings which can be shared by several parses, so that object
+/// keys repeated across documents are only stored once.
+///
+/// See `JsonParser::with_interner`.
+#[derive(Default)]
+pub struct Interner {
+    inner: RefCell<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    /// The keys borrow from `strings`. They are never handed out with the
+    /// `'static` lifetime, only with the lifetime of the `Interner`.
+    symbols: HashMap<&'static str, Symbol>,
+    strings: Vec<Box<str>>,
+    lookups: usize,
+    hits: usize,
+}
+
+impl Interner {
+    pub fn new() -> Interner {
+        Interner::default()
+    }
+
+    pub fn intern(&self, s: &str) -> Symbol {
+        let mut inner = self.inner.borrow_mut();
+        inner.lookups += 1;
+
+        if let Some(&symbol) = inner.symbols.get(s) {
+            inner.hits += 1;
+            return symbol;
+        }
+
+        let symbol = Symbol(inner.strings.len() as u32);
+        let string: Box<str> = s.into();
+        // The content of a `Box` doesn't move when the box does, and strings
+        // are never removed, so this reference stays valid as long as the
+        // interner itself.
+        let key: &'static str = unsafe { &*(&*string as *const str) };
+
+        inner.strings.push(string);
+        inner.symbols.insert(key, symbol);
+        symbol
+    }
+
+    /// The symbol of `s`, if it has already been interned.
+    pub fn get(&self, s: &str) -> Option<Symbol> {
+        self.inner.borrow().symbols.get(s).cloned()
+    }
+
+    /// The string of `symbol`.
+    ///
+    /// # Panics
+    ///
+    /// Symbols aren't tied to the interner which created them: resolving a
+    /// symbol from another interner may return an unrelated string, or panic
+    /// if it is out of range.
+    pub fn resolve(&self, symbol: Symbol) -> &str {
+        let inner = self.inner.borrow();
+        let string: &str = &inner.strings[symbol.0 as usize];
+        // See `intern`.
+        unsafe { &*(string as *const str) }
+    }
+
+    /// Intern `s` and return the interned copy.
+    pub(crate) fn intern_str(&self, s: &str) -> &str {
+        self.resolve(self.intern(s))
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.borrow().strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn stats(&self) -> InternerStats {
+        let inner = self.inner.borrow();
+
+        InternerStats {
+            symbols: inner.strings.len(),
+            lookups: inner.lookups,
+            hits: inner.hits,
+        }
+    }
+}
@@ -0,0 +1,1016 @@
+//! JSONPath (RFC 9535) queries, e.g. `$.store.book[?@.price < 10].title`.
+//!
+//! The `length()`, `count()` and `value()` function extensions are supported;
+//! `match()` and `search()` are not.
+
+use std::borrow::Cow;
+use std::char;
+use std::fmt::Write;
+use JsonValue;
+
+pub type JsonPathResult<T> = Result<T, JsonPathError>;
+
+#[derive(PartialEq, Debug)]
+pub struct JsonPathError {
+    /// Byte offset of the error in the query.
+    pub position: usize,
+    pub message: &'static str,
+}
+
+/// A compiled JSONPath query.
+#[derive(PartialEq, Debug)]
+pub struct JsonPath {
+    query: Query,
+}
+
+/// A node selected by a query.
+#[derive(PartialEq, Debug)]
+pub struct JsonPathMatch<'v, 'a: 'v> {
+    /// The normalized path of the node, e.g. `$['store']['book'][0]`.
+    pub path: String,
+    pub value: &'v JsonValue<'a>,
+}
+
+impl JsonPath {
+    /// Parse a JSONPath query.
+    pub fn parse(query: &str) -> JsonPathResult<JsonPath> {
+        let mut parser = QueryParser {
+            input: query.as_bytes(),
+            idx: 0,
+        };
+
+        if parser.current_byte() != Some(b'$') {
+            return Err(parser.error("expected `$`"));
+        }
+
+        let query = try!(parser.parse_query());
+
+        if parser.idx != parser.input.len() {
+            return Err(parser.error("unexpected character"));
+        }
+
+        Ok(JsonPath { query: query })
+    }
+
+    /// Select the nodes of `value` matched by this query, in order.
+    ///
+    /// `Raw` values which the query descends into are parsed, see
+    /// `RawValue::value`. Selected values are returned as they are, so they
+    /// may be `Raw`.
+    pub fn query<'v, 'a>(&self, value: &'v JsonValue<'a>) -> Vec<JsonPathMatch<'v, 'a>> {
+        evaluate_query(&self.query, value, value)
+            .into_iter()
+            .map(|node| {
+                JsonPathMatch {
+                    path: normalized_path(&node.path),
+                    value: node.value,
+                }
+            })
+            .collect()
+    }
+}
+
+#[derive(PartialEq, Debug)]
+struct Query {
+    /// Whether the query starts with `@` rather than `$`.
+    relative: bool,
+    segments: Vec<Segment>,
+}
+
+impl Query {
+    /// Whether the query selects at most one node.
+    fn is_singular(&self) -> bool {
+        self.segments.iter().all(|segment| {
+            match *segment {
+                Segment::Child(ref selectors) => {
+                    selectors.len() == 1 &&
+                    matches!(selectors[0], Selector::Name(_) | Selector::Index(_))
+                }
+                Segment::Descendant(_) => false,
+            }
+        })
+    }
+}
+
+#[derive(PartialEq, Debug)]
+enum Segment {
+    Child(Vec<Selector>),
+    Descendant(Vec<Selector>),
+}
+
+#[derive(PartialEq, Debug)]
+enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice(Option<i64>, Option<i64>, Option<i64>),
+    Filter(LogicalExpr),
+}
+
+#[derive(PartialEq, Debug)]
+enum LogicalExpr {
+    Or(Vec<LogicalExpr>),
+    And(Vec<LogicalExpr>),
+    Not(Box<LogicalExpr>),
+    Comparison(Comparable, ComparisonOp, Comparable),
+    Exists(Query),
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+enum ComparisonOp {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+#[derive(PartialEq, Debug)]
+enum Comparable {
+    Literal(JsonValue<'static>),
+    /// Always a singular query.
+    Query(Query),
+    Function(Function),
+}
+
+#[derive(PartialEq, Debug)]
+enum Function {
+    Length(Box<Comparable>),
+    Count(Query),
+    Value(Query),
+}
+
+// Parser
+
+struct QueryParser<'q> {
+    input: &'q [u8],
+    idx: usize,
+}
+
+fn is_blank(b: u8) -> bool {
+    b == b' ' || b == b'\t' || b == b'\n' || b == b'\r'
+}
+
+fn is_name_first(b: u8) -> bool {
+    b.is_ascii_alphabetic() || b == b'_' || b >= 0x80
+}
+
+impl<'q> QueryParser<'q> {
+    fn error(&self, message: &'static str) -> JsonPathError {
+        JsonPathError {
+            position: self.idx,
+            message: message,
+        }
+    }
+
+    fn current_byte(&self) -> Option<u8> {
+        self.input.get(self.idx).cloned()
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        self.input[self.idx..].starts_with(s.as_bytes())
+    }
+
+    fn expect(&mut self, expected: u8, message: &'static str) -> JsonPathResult<()> {
+        if self.current_byte() != Some(expected) {
+            return Err(self.error(message));
+        }
+
+        self.idx += 1;
+        Ok(())
+    }
+
+    fn skip_blanks(&mut self) {
+        while self.current_byte().is_some_and(is_blank) {
+            self.idx += 1;
+        }
+    }
+
+    /// Parse `$` or `@` followed by any number of segments.
+    fn parse_query(&mut self) -> JsonPathResult<Query> {
+        let relative = self.current_byte() == Some(b'@');
+        self.idx += 1;
+
+        let mut segments = Vec::new();
+
+        loop {
+            // Blank space is allowed between segments, but it may also
+            // precede an operator in a filter expression.
+            let segment_start = self.idx;
+            self.skip_blanks();
+
+            match self.current_byte() {
+                Some(b'[') | Some(b'.') => segments.push(try!(self.parse_segment())),
+                _ => {
+                    self.idx = segment_start;
+                    break;
+                }
+            }
+        }
+
+        Ok(Query {
+            relative: relative,
+            segments: segments,
+        })
+    }
+
+    fn parse_segment(&mut self) -> JsonPathResult<Segment> {
+        if self.starts_with("..") {
+            self.idx += 2;
+
+            let selectors = match self.current_byte() {
+                Some(b'[') => try!(self.parse_bracketed_selection()),
+                _ => vec![try!(self.parse_shorthand())],
+            };
+
+            return Ok(Segment::Descendant(selectors));
+        }
+
+        if self.current_byte() == Some(b'.') {
+            self.idx += 1;
+            return Ok(Segment::Child(vec![try!(self.parse_shorthand())]));
+        }
+
+        Ok(Segment::Child(try!(self.parse_bracketed_selection())))
+    }
+
+    /// Parse the `*` or `name` following `.` or `..`.
+    fn parse_shorthand(&mut self) -> JsonPathResult<Selector> {
+        match self.current_byte() {
+            Some(b'*') => {
+                self.idx += 1;
+                Ok(Selector::Wildcard)
+            }
+            Some(b) if is_name_first(b) => {
+                let start = self.idx;
+
+                while self.current_byte().is_some_and(|b| is_name_first(b) || b.is_ascii_digit()) {
+                    self.idx += 1;
+                }
+
+                let name = String::from_utf8_lossy(&self.input[start..self.idx]).into_owned();
+                Ok(Selector::Name(name))
+            }
+            _ => Err(self.error("expected a member name or `*`")),
+        }
+    }
+
+    fn parse_bracketed_selection(&mut self) -> JsonPathResult<Vec<Selector>> {
+        try!(self.expect(b'[', "expected `[`"));
+        let mut selectors = Vec::new();
+
+        loop {
+            self.skip_blanks();
+            selectors.push(try!(self.parse_selector()));
+            self.skip_blanks();
+
+            match self.current_byte() {
+                Some(b',') => self.idx += 1,
+                Some(b']') => {
+                    self.idx += 1;
+                    return Ok(selectors);
+                }
+                _ => return Err(self.error("expected `,` or `]`")),
+            }
+        }
+    }
+
+    fn parse_selector(&mut self) -> JsonPathResult<Selector> {
+        match self.current_byte() {
+            Some(b'\'') | Some(b'"') => Ok(Selector::Name(try!(self.parse_string_literal()))),
+            Some(b'*') => {
+                self.idx += 1;
+                Ok(Selector::Wildcard)
+            }
+            Some(b'?') => {
+                self.idx += 1;
+                self.skip_blanks();
+                Ok(Selector::Filter(try!(self.parse_logical_or())))
+            }
+            Some(b'-') | Some(b'0'...b'9') | Some(b':') => self.parse_index_or_slice(),
+            _ => Err(self.error("expected a selector")),
+        }
+    }
+
+    fn parse_index_or_slice(&mut self) -> JsonPathResult<Selector> {
+        let start = if self.current_byte() == Some(b':') {
+            None
+        } else {
+            Some(try!(self.parse_int()))
+        };
+
+        // Don't consume blanks yet: `[1 ]` is an index.
+        let after_start = self.idx;
+        self.skip_blanks();
+
+        if self.current_byte() != Some(b':') {
+            self.idx = after_start;
+            return Ok(Selector::Index(start.unwrap()));
+        }
+
+        self.idx += 1;
+        self.skip_blanks();
+
+        let end = match self.current_byte() {
+            Some(b'-') | Some(b'0'...b'9') => {
+                let end = try!(self.parse_int());
+                self.skip_blanks();
+                Some(end)
+            }
+            _ => None,
+        };
+
+        let mut step = None;
+
+        if self.current_byte() == Some(b':') {
+            self.idx += 1;
+            self.skip_blanks();
+
+            if let Some(b'-') | Some(b'0'...b'9') = self.current_byte() {
+                step = Some(try!(self.parse_int()));
+            }
+        }
+
+        Ok(Selector::Slice(start, end, step))
+    }
+
+    fn parse_int(&mut self) -> JsonPathResult<i64> {
+        let start = self.idx;
+
+        if self.current_byte() == Some(b'-') {
+            self.idx += 1;
+        }
+
+        let digits_start = self.idx;
+
+        while self.current_byte().is_some_and(|b| b.is_ascii_digit()) {
+            self.idx += 1;
+        }
+
+        let digits = &self.input[digits_start..self.idx];
+
+        if digits.is_empty() || (digits[0] == b'0' && (digits.len() > 1 || digits_start > start)) {
+            self.idx = start;
+            return Err(self.error("invalid integer"));
+        }
+
+        let s = String::from_utf8_lossy(&self.input[start..self.idx]);
+
+        match s.parse::<i64>() {
+            Ok(n) if -(1 << 53) < n && n < (1 << 53) => Ok(n),
+            _ => {
+                self.idx = start;
+                Err(self.error("integer out of range"))
+            }
+        }
+    }
+
+    fn parse_hex4(&mut self) -> JsonPathResult<u32> {
+        let mut n = 0;
+
+        for _ in 0..4 {
+            let digit = match self.current_byte() {
+                Some(b @ b'0'...b'9') => b - b'0',
+                Some(b @ b'a'...b'f') => b - b'a' + 10,
+                Some(b @ b'A'...b'F') => b - b'A' + 10,
+                _ => return Err(self.error("invalid unicode escape")),
+            };
+            n = n * 16 + digit as u32;
+            self.idx += 1;
+        }
+
+        Ok(n)
+    }
+
+    fn parse_string_literal(&mut self) -> JsonPathResult<String> {
+        let quote = self.current_byte().unwrap();
+        self.idx += 1;
+
+        let mut bytes = Vec::new();
+
+        loop {
+            match self.current_byte() {
+                None => return Err(self.error("unterminated string")),
+                Some(b) if b == quote => {
+                    self.idx += 1;
+                    break;
+                }
+                Some(b'\\') => {
+                    self.idx += 1;
+
+                    let c = match self.current_byte() {
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'/') => '/',
+                        Some(b'\\') => '\\',
+                        Some(b) if b == quote => b as char,
+                        Some(b'u') => {
+                            self.idx += 1;
+                            let mut code_point = try!(self.parse_hex4());
+
+                            if (0xD800..0xDC00).contains(&code_point) {
+                                if !self.starts_with("\\u") {
+                                    return Err(self.error("unpaired surrogate"));
+                                }
+                                self.idx += 2;
+
+                                let low = try!(self.parse_hex4());
+                                if !(0xDC00..0xE000).contains(&low) {
+                                    return Err(self.error("unpaired surrogate"));
+                                }
+
+                                code_point = 0x10000 + ((code_point - 0xD800) << 10) +
+                                             (low - 0xDC00);
+                            }
+
+                            match char::from_u32(code_point) {
+                                Some(c) => {
+                                    let mut buf = [0; 4];
+                                    bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                                    continue;
+                                }
+                                None => return Err(self.error("unpaired surrogate")),
+                            }
+                        }
+                        _ => return Err(self.error("invalid escape")),
+                    };
+
+                    bytes.push(c as u8);
+                }
+                Some(b) if b < 0x20 => return Err(self.error("unescaped control character")),
+                Some(b) => bytes.push(b),
+            }
+
+            self.idx += 1;
+        }
+
+        // The query is a `&str` and only valid code points are decoded.
+        Ok(String::from_utf8(bytes).unwrap())
+    }
+
+    fn parse_logical_or(&mut self) -> JsonPathResult<LogicalExpr> {
+        let mut operands = vec![try!(self.parse_logical_and())];
+
+        loop {
+            let before = self.idx;
+            self.skip_blanks();
+
+            if !self.starts_with("||") {
+                self.idx = before;
+                break;
+            }
+
+            self.idx += 2;
+            self.skip_blanks();
+            operands.push(try!(self.parse_logical_and()));
+        }
+
+        Ok(if operands.len() == 1 {
+            operands.pop().unwrap()
+        } else {
+            LogicalExpr::Or(operands)
+        })
+    }
+
+    fn parse_logical_and(&mut self) -> JsonPathResult<LogicalExpr> {
+        let mut operands = vec![try!(self.parse_basic_expr())];
+
+        loop {
+            let before = self.idx;
+            self.skip_blanks();
+
+            if !self.starts_with("&&") {
+                self.idx = before;
+                break;
+            }
+
+            self.idx += 2;
+            self.skip_blanks();
+            operands.push(try!(self.parse_basic_expr()));
+        }
+
+        Ok(if operands.len() == 1 {
+            operands.pop().unwrap()
+        } else {
+            LogicalExpr::And(operands)
+        })
+    }
+
+    fn parse_basic_expr(&mut self) -> JsonPathResult<LogicalExpr> {
+        if self.current_byte() == Some(b'!') {
+            self.idx += 1;
+            self.skip_blanks();
+
+            let operand = match self.current_byte() {
+                Some(b'(') => try!(self.parse_paren_expr()),
+                _ => try!(self.parse_test_expr()),
+            };
+
+            return Ok(LogicalExpr::Not(Box::new(operand)));
+        }
+
+        if self.current_byte() == Some(b'(') {
+            return self.parse_paren_expr();
+        }
+
+        let start = self.idx;
+        let lhs = try!(self.parse_comparable_or_query());
+
+        let before_op = self.idx;
+        self.skip_blanks();
+
+        let op = match self.parse_comparison_op() {
+            Some(op) => op,
+            None => {
+                self.idx = before_op;
+
+                return match lhs {
+                    Operand::Query(query) => Ok(LogicalExpr::Exists(query)),
+                    _ => {
+                        self.idx = start;
+                        Err(self.error("expected a test or comparison expression"))
+                    }
+                };
+            }
+        };
+
+        self.skip_blanks();
+        let rhs_start = self.idx;
+        let rhs = try!(self.parse_comparable_or_query());
+
+        let lhs = try!(self.to_comparable(lhs, start));
+        let rhs = try!(self.to_comparable(rhs, rhs_start));
+
+        Ok(LogicalExpr::Comparison(lhs, op, rhs))
+    }
+
+    fn parse_paren_expr(&mut self) -> JsonPathResult<LogicalExpr> {
+        try!(self.expect(b'(', "expected `(`"));
+        self.skip_blanks();
+        let expr = try!(self.parse_logical_or());
+        self.skip_blanks();
+        try!(self.expect(b')', "expected `)`"));
+
+        Ok(expr)
+    }
+
+    fn parse_test_expr(&mut self) -> JsonPathResult<LogicalExpr> {
+        match try!(self.parse_comparable_or_query()) {
+            Operand::Query(query) => Ok(LogicalExpr::Exists(query)),
+            _ => Err(self.error("expected a test expression")),
+        }
+    }
+
+    fn parse_comparison_op(&mut self) -> Option<ComparisonOp> {
+        let ops = [("==", ComparisonOp::Equal),
+                   ("!=", ComparisonOp::NotEqual),
+                   ("<=", ComparisonOp::LessOrEqual),
+                   (">=", ComparisonOp::GreaterOrEqual),
+                   ("<", ComparisonOp::Less),
+                   (">", ComparisonOp::Greater)];
+
+        for &(token, op) in &ops {
+            if self.starts_with(token) {
+                self.idx += token.len();
+                return Some(op);
+            }
+        }
+
+        None
+    }
+
+    /// Check that an operand of a comparison is well-typed.
+    fn to_comparable(&self, operand: Operand, position: usize) -> JsonPathResult<Comparable> {
+        match operand {
+            Operand::Query(query) => {
+                if query.is_singular() {
+                    Ok(Comparable::Query(query))
+                } else {
+                    Err(JsonPathError {
+                        position: position,
+                        message: "only singular queries can be compared",
+                    })
+                }
+            }
+            Operand::Comparable(comparable) => Ok(comparable),
+        }
+    }
+
+    fn parse_comparable_or_query(&mut self) -> JsonPathResult<Operand> {
+        match self.current_byte() {
+            Some(b'@') | Some(b'$') => Ok(Operand::Query(try!(self.parse_query()))),
+            Some(b'\'') | Some(b'"') => {
+                let s = try!(self.parse_string_literal());
+                Ok(Operand::Comparable(Comparable::Literal(JsonValue::String(Cow::Owned(s)))))
+            }
+            Some(b'-') | Some(b'0'...b'9') => {
+                Ok(Operand::Comparable(Comparable::Literal(JsonValue::Number(try!(self.parse_number())))))
+            }
+            Some(b'a'...b'z') => {
+                let start = self.idx;
+
+                while let Some(b'a'...b'z') | Some(b'_') | Some(b'0'...b'9') = self.current_byte() {
+                    self.idx += 1;
+                }
+
+                let name = &self.input[start..self.idx];
+
+                if self.current_byte() == Some(b'(') {
+                    return Ok(Operand::Comparable(Comparable::Function(try!(self.parse_function(name, start)))));
+                }
+
+                let literal = match name {
+                    b"true" => JsonValue::Boolean(true),
+                    b"false" => JsonValue::Boolean(false),
+                    b"null" => JsonValue::Null,
+                    _ => {
+                        self.idx = start;
+                        return Err(self.error("unexpected identifier"));
+                    }
+                };
+
+                Ok(Operand::Comparable(Comparable::Literal(literal)))
+            }
+            _ => Err(self.error("expected a literal, query or function")),
+        }
+    }
+
+    fn parse_number(&mut self) -> JsonPathResult<f64> {
+        let start = self.idx;
+
+        if self.current_byte() == Some(b'-') {
+            self.idx += 1;
+        }
+
+        let int_start = self.idx;
+        while self.current_byte().is_some_and(|b| b.is_ascii_digit()) {
+            self.idx += 1;
+        }
+
+        let int_len = self.idx - int_start;
+        if int_len == 0 || (int_len > 1 && self.input[int_start] == b'0') {
+            self.idx = start;
+            return Err(self.error("invalid number"));
+        }
+
+        if self.current_byte() == Some(b'.') {
+            self.idx += 1;
+            let frac_start = self.idx;
+
+            while self.current_byte().is_some_and(|b| b.is_ascii_digit()) {
+                self.idx += 1;
+            }
+
+            if self.idx == frac_start {
+                return Err(self.error("invalid number"));
+            }
+        }
+
+        if let Some(b'e') | Some(b'E') = self.current_byte() {
+            self.idx += 1;
+
+            if let Some(b'+') | Some(b'-') = self.current_byte() {
+                self.idx += 1;
+            }
+
+            let exp_start = self.idx;
+            while self.current_byte().is_some_and(|b| b.is_ascii_digit()) {
+                self.idx += 1;
+            }
+
+            if self.idx == exp_start {
+                return Err(self.error("invalid number"));
+            }
+        }
+
+        let s = String::from_utf8_lossy(&self.input[start..self.idx]);
+        Ok(s.parse().unwrap())
+    }
+
+    fn parse_function(&mut self, name: &[u8], start: usize) -> JsonPathResult<Function> {
+        try!(self.expect(b'(', "expected `(`"));
+        self.skip_blanks();
+
+        let arg_start = self.idx;
+        let arg = try!(self.parse_comparable_or_query());
+
+        self.skip_blanks();
+        try!(self.expect(b')', "expected `)`"));
+
+        let error = |message| {
+            Err(JsonPathError {
+                position: arg_start,
+                message: message,
+            })
+        };
+
+        match (name, arg) {
+            (b"length", arg) => {
+                Ok(Function::Length(Box::new(try!(self.to_comparable(arg, arg_start)))))
+            }
+            (b"count", Operand::Query(query)) => Ok(Function::Count(query)),
+            (b"value", Operand::Query(query)) => Ok(Function::Value(query)),
+            (b"count", _) | (b"value", _) => error("expected a query"),
+            _ => {
+                Err(JsonPathError {
+                    position: start,
+                    message: "unknown function",
+                })
+            }
+        }
+    }
+}
+
+/// A parsed filter operand, before well-typedness is checked.
+enum Operand {
+    Query(Query),
+    Comparable(Comparable),
+}
+
+// Evaluation
+
+#[derive(Clone, Debug)]
+enum PathElement<'v> {
+    Name(&'v str),
+    Index(usize),
+}
+
+struct Node<'v, 'a: 'v> {
+    path: Vec<PathElement<'v>>,
+    value: &'v JsonValue<'a>,
+}
+
+fn normalized_path(path: &[PathElement]) -> String {
+    let mut output = String::from("$");
+
+    for element in path {
+        match *element {
+            PathElement::Index(idx) => write!(output, "[{}]", idx).unwrap(),
+            PathElement::Name(name) => {
+                output.push_str("['");
+
+                for c in name.chars() {
+                    match c {
+                        '\u{8}' => output.push_str("\\b"),
+                        '\u{c}' => output.push_str("\\f"),
+                        '\n' => output.push_str("\\n"),
+                        '\r' => output.push_str("\\r"),
+                        '\t' => output.push_str("\\t"),
+                        '\'' => output.push_str("\\'"),
+                        '\\' => output.push_str("\\\\"),
+                        c if c < '\u{20}' => write!(output, "\\u{:04x}", c as u32).unwrap(),
+                        c => output.push(c),
+                    }
+                }
+
+                output.push_str("']");
+            }
+        }
+    }
+
+    output
+}
+
+fn evaluate_query<'v, 'a>(query: &Query,
+                          root: &'v JsonValue<'a>,
+                          current: &'v JsonValue<'a>)
+                          -> Vec<Node<'v, 'a>> {
+    let start = if query.relative { current } else { root };
+
+    let mut nodes = vec![Node {
+                             path: Vec::new(),
+                             value: start,
+                         }];
+
+    for segment in &query.segments {
+        let mut output = Vec::new();
+
+        for node in &nodes {
+            match *segment {
+                Segment::Child(ref selectors) => {
+                    select_children(selectors, node, root, &mut output);
+                }
+                Segment::Descendant(ref selectors) => {
+                    let mut descendants = vec![Node {
+                                                   path: node.path.clone(),
+                                                   value: node.value,
+                                               }];
+                    collect_descendants(node, &mut descendants);
+
+                    for descendant in &descendants {
+                        select_children(selectors, descendant, root, &mut output);
+                    }
+                }
+            }
+        }
+
+        nodes = output;
+    }
+
+    nodes
+}
+
+/// Push the descendants of `node` in document order.
+fn collect_descendants<'v, 'a>(node: &Node<'v, 'a>, output: &mut Vec<Node<'v, 'a>>) {
+    for child in children(node) {
+        output.push(Node {
+            path: child.path.clone(),
+            value: child.value,
+        });
+        collect_descendants(&child, output);
+    }
+}
+
+fn child_node<'v, 'a>(parent: &Node<'v, 'a>,
+                      element: PathElement<'v>,
+                      value: &'v JsonValue<'a>)
+                      -> Node<'v, 'a> {
+    let mut path = parent.path.clone();
+    path.push(element);
+
+    Node {
+        path: path,
+        value: value,
+    }
+}
+
+fn children<'v, 'a>(node: &Node<'v, 'a>) -> Vec<Node<'v, 'a>> {
+    match *node.value.resolved() {
+        JsonValue::Array(ref vec) => {
+            vec.iter()
+               .enumerate()
+               .map(|(idx, value)| child_node(node, PathElement::Index(idx), value))
+               .collect()
+        }
+        JsonValue::Object(ref map) => {
+            map.iter()
+               .map(|(name, value)| child_node(node, PathElement::Name(name), value))
+               .collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn normalize_index(idx: i64, len: i64) -> i64 {
+    if idx >= 0 { idx } else { len + idx }
+}
+
+fn select_children<'v, 'a>(selectors: &[Selector],
+                           node: &Node<'v, 'a>,
+                           root: &'v JsonValue<'a>,
+                           output: &mut Vec<Node<'v, 'a>>) {
+    for selector in selectors {
+        match *selector {
+            Selector::Name(ref name) => {
+                if let JsonValue::Object(ref map) = *node.value.resolved() {
+                    if let Some((key, value)) = map.get_key_value(&**name) {
+                        output.push(child_node(node, PathElement::Name(key), value));
+                    }
+                }
+            }
+            Selector::Wildcard => output.extend(children(node)),
+            Selector::Index(idx) => {
+                if let JsonValue::Array(ref vec) = *node.value.resolved() {
+                    let idx = normalize_index(idx, vec.len() as i64);
+
+                    if 0 <= idx && idx < vec.len() as i64 {
+                        let idx = idx as usize;
+                        output.push(child_node(node, PathElement::Index(idx), &vec[idx]));
+                    }
+                }
+            }
+            Selector::Slice(start, end, step) => {
+                if let JsonValue::Array(ref vec) = *node.value.resolved() {
+                    for idx in slice_indices(start, end, step, vec.len() as i64) {
+                        output.push(child_node(node, PathElement::Index(idx), &vec[idx]));
+                    }
+                }
+            }
+            Selector::Filter(ref expr) => {
+                for child in children(node) {
+                    if evaluate_logical(expr, root, child.value) {
+                        output.push(child);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn slice_indices(start: Option<i64>, end: Option<i64>, step: Option<i64>, len: i64) -> Vec<usize> {
+    let step = step.unwrap_or(1);
+    let mut indices = Vec::new();
+
+    if step > 0 {
+        let start = normalize_index(start.unwrap_or(0), len);
+        let end = normalize_index(end.unwrap_or(len), len);
+        let lower = start.max(0).min(len);
+        let upper = end.max(0).min(len);
+
+        let mut i = lower;
+        while i < upper {
+            indices.push(i as usize);
+            i += step;
+        }
+    } else if step < 0 {
+        let start = normalize_index(start.unwrap_or(len - 1), len);
+        let end = normalize_index(end.unwrap_or(-len - 1), len);
+        let upper = start.max(-1).min(len - 1);
+        let lower = end.max(-1).min(len - 1);
+
+        let mut i = upper;
+        while lower < i {
+            indices.push(i as usize);
+            i += step;
+        }
+    }
+
+    indices
+}
+
+fn evaluate_logical<'v, 'a>(expr: &LogicalExpr,
+                            root: &'v JsonValue<'a>,
+                            current: &'v JsonValue<'a>)
+                            -> bool {
+    match *expr {
+        LogicalExpr::Or(ref operands) => {
+            operands.iter().any(|operand| evaluate_logical(operand, root, current))
+        }
+        LogicalExpr::And(ref operands) => {
+            operands.iter().all(|operand| evaluate_logical(operand, root, current))
+        }
+        LogicalExpr::Not(ref operand) => !evaluate_logical(operand, root, current),
+        LogicalExpr::Exists(ref query) => !evaluate_query(query, root, current).is_empty(),
+        LogicalExpr::Comparison(ref lhs, op, ref rhs) => {
+            let lhs = evaluate_comparable(lhs, root, current);
+            let rhs = evaluate_comparable(rhs, root, current);
+
+            match op {
+                ComparisonOp::Equal => lhs == rhs,
+                ComparisonOp::NotEqual => lhs != rhs,
+                ComparisonOp::Less => less_than(&lhs, &rhs),
+                ComparisonOp::LessOrEqual => less_than(&lhs, &rhs) || lhs == rhs,
+                ComparisonOp::Greater => less_than(&rhs, &lhs),
+                ComparisonOp::GreaterOrEqual => less_than(&rhs, &lhs) || lhs == rhs,
+            }
+        }
+    }
+}
+
+/// `None` stands for `Nothing`, the result of an empty query.
+fn evaluate_comparable<'e, 'v: 'e, 'a>(comparable: &'e Comparable,
+                                       root: &'v JsonValue<'a>,
+                                       current: &'v JsonValue<'a>)
+                                       -> Option<Cow<'e, JsonValue<'a>>> {
+    match *comparable {
+        Comparable::Literal(ref value) => Some(Cow::Borrowed(value)),
+        Comparable::Query(ref query) => {
+            evaluate_query(query, root, current).pop().map(|node| Cow::Borrowed(node.value))
+        }
+        Comparable::Function(Function::Length(ref arg)) => {
+            let length = match evaluate_comparable(arg, root, current) {
+                Some(value) => {
+                    match *value.resolved() {
+                        JsonValue::String(ref s) => s.chars().count(),
+                        JsonValue::Array(ref vec) => vec.len(),
+                        JsonValue::Object(ref map) => map.len(),
+                        _ => return None,
+                    }
+                }
+                None => return None,
+            };
+
+            Some(Cow::Owned(JsonValue::Number(length as f64)))
+        }
+        Comparable::Function(Function::Count(ref query)) => {
+            let count = evaluate_query(query, root, current).len();
+            Some(Cow::Owned(JsonValue::Number(count as f64)))
+        }
+        Comparable::Function(Function::Value(ref query)) => {
+            let mut nodes = evaluate_query(query, root, current);
+
+            if nodes.len() == 1 {
+                nodes.pop().map(|node| Cow::Borrowed(node.value))
+            } else {
+                None
+            }
+        }
+    }
+}
+
+fn less_than(lhs: &Option<Cow<JsonValue>>, rhs: &Option<Cow<JsonValue>>) -> bool {
+    match (lhs, rhs) {
+        (&Some(ref lhs), &Some(ref rhs)) => {
+            match (&**lhs, &**rhs) {
+                (&JsonValue::Number(a), &JsonValue::Number(b)) => a < b,
+                (&JsonValue::String(ref a), &JsonValue::String(ref b)) => a < b,
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
@@ -1,7 +1,20 @@
 use std::collections::HashMap;
+use std::collections::hash_map;
 use std::borrow::Cow;
+use std::fmt;
+use std::mem;
+use std::slice;
+use RawValue;
 
-#[derive(PartialEq, Debug)]
+// `Drop`, `Clone`, `PartialEq` and `Debug` are implemented with explicit
+// stacks instead of being derived, since the derived implementations recurse
+// and overflow the native stack on deeply nested values.
+
+/// A parsed JSON value.
+///
+/// Since `JsonValue` implements `Drop`, the payload of a variant can't be
+/// moved out by pattern matching (error E0509): use `into_string`,
+/// `into_array` or `into_object` instead.
 pub enum JsonValue<'a> {
     String(Cow<'a, str>),
     Number(f64),
@@ -9,4 +22,402 @@ pub enum JsonValue<'a> {
     Object(HashMap<Cow<'a, str>, JsonValue<'a>>),
     Array(Vec<JsonValue<'a>>),
     Null,
+    /// An object or array which was validated but not parsed yet, see
+    /// `ParserOptions::lazy_depth`.
+    Raw(RawValue<'a>),
+    /// A value which couldn't be parsed, see `JsonParser::parse_recovering`.
+    Invalid,
+}
+
+impl<'a> JsonValue<'a> {
+    fn has_children(&self) -> bool {
+        match *self {
+            JsonValue::Object(ref members) => !members.is_empty(),
+            JsonValue::Array(ref elements) => !elements.is_empty(),
+            _ => false,
+        }
+    }
+
+    /// Move the nested containers out of this value, onto `stack`.
+    fn take_children(&mut self, stack: &mut Vec<JsonValue<'a>>) {
+        match *self {
+            JsonValue::Object(ref mut members) => {
+                stack.extend(members.drain().map(|(_, value)| value).filter(JsonValue::has_children))
+            }
+            JsonValue::Array(ref mut elements) => {
+                stack.extend(elements.drain(..).filter(JsonValue::has_children))
+            }
+            _ => {}
+        }
+    }
+
+    fn children<'v>(&'v self) -> Children<'v, 'a> {
+        match *self {
+            JsonValue::Object(ref members) => Children::Object(members.iter()),
+            JsonValue::Array(ref elements) => Children::Array(elements.iter()),
+            _ => Children::Array([].iter()),
+        }
+    }
+}
+
+impl<'a> JsonValue<'a> {
+    /// The content of a `String`, or `None` for other variants.
+    pub fn into_string(mut self) -> Option<Cow<'a, str>> {
+        match self {
+            JsonValue::String(ref mut s) => Some(mem::take(s)),
+            _ => None,
+        }
+    }
+
+    /// The elements of an `Array`, or `None` for other variants.
+    pub fn into_array(mut self) -> Option<Vec<JsonValue<'a>>> {
+        match self {
+            JsonValue::Array(ref mut elements) => Some(mem::take(elements)),
+            _ => None,
+        }
+    }
+
+    /// The members of an `Object`, or `None` for other variants.
+    pub fn into_object(mut self) -> Option<HashMap<Cow<'a, str>, JsonValue<'a>>> {
+        match self {
+            JsonValue::Object(ref mut members) => Some(mem::take(members)),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> Drop for JsonValue<'a> {
+    fn drop(&mut self) {
+        if !self.has_children() {
+            return;
+        }
+
+        // Each value on the stack is dropped once its own nested containers
+        // were moved out, so the drops don't recurse.
+        let mut stack = Vec::new();
+        self.take_children(&mut stack);
+
+        while let Some(mut value) = stack.pop() {
+            value.take_children(&mut stack);
+        }
+    }
+}
+
+/// The members or elements of a container, with the keys of members.
+enum Children<'v, 'a: 'v> {
+    Object(hash_map::Iter<'v, Cow<'a, str>, JsonValue<'a>>),
+    Array(slice::Iter<'v, JsonValue<'a>>),
+}
+
+impl<'v, 'a> Iterator for Children<'v, 'a> {
+    type Item = (Option<&'v Cow<'a, str>>, &'v JsonValue<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match *self {
+            Children::Object(ref mut members) => members.next().map(|(key, value)| (Some(key), value)),
+            Children::Array(ref mut elements) => elements.next().map(|value| (None, value)),
+        }
+    }
+}
+
+/// A container being cloned, with the members or elements cloned so far.
+enum CloneFrame<'v, 'a: 'v> {
+    Object(hash_map::Iter<'v, Cow<'a, str>, JsonValue<'a>>,
+           HashMap<Cow<'a, str>, JsonValue<'a>>,
+           Cow<'a, str>),
+    Array(slice::Iter<'v, JsonValue<'a>>, Vec<JsonValue<'a>>),
+}
+
+impl<'v, 'a> CloneFrame<'v, 'a> {
+    fn push(&mut self, value: JsonValue<'a>) {
+        match *self {
+            CloneFrame::Object(_, ref mut members, ref mut key) => {
+                members.insert(mem::replace(key, Cow::Borrowed("")), value);
+            }
+            CloneFrame::Array(_, ref mut elements) => elements.push(value),
+        }
+    }
+
+    /// The next member or element to clone.
+    fn next(&mut self) -> Option<&'v JsonValue<'a>> {
+        match *self {
+            CloneFrame::Object(ref mut source, _, ref mut key) => {
+                source.next().map(|(next_key, value)| {
+                    *key = next_key.clone();
+                    value
+                })
+            }
+            CloneFrame::Array(ref mut source, _) => source.next(),
+        }
+    }
+
+    fn into_value(self) -> JsonValue<'a> {
+        match self {
+            CloneFrame::Object(_, members, _) => JsonValue::Object(members),
+            CloneFrame::Array(_, elements) => JsonValue::Array(elements),
+        }
+    }
+}
+
+impl<'a> Clone for JsonValue<'a> {
+    fn clone(&self) -> JsonValue<'a> {
+        let mut stack = Vec::new();
+        let mut source = self;
+
+        loop {
+            let mut value = match *source {
+                JsonValue::String(ref s) => Some(JsonValue::String(s.clone())),
+                JsonValue::Number(n) => Some(JsonValue::Number(n)),
+                JsonValue::Boolean(b) => Some(JsonValue::Boolean(b)),
+                JsonValue::Null => Some(JsonValue::Null),
+                JsonValue::Raw(ref raw) => Some(JsonValue::Raw(raw.clone())),
+                JsonValue::Invalid => Some(JsonValue::Invalid),
+                JsonValue::Object(ref members) => {
+                    stack.push(CloneFrame::Object(members.iter(),
+                                                  HashMap::with_capacity(members.len()),
+                                                  Cow::Borrowed("")));
+                    None
+                }
+                JsonValue::Array(ref elements) => {
+                    stack.push(CloneFrame::Array(elements.iter(),
+                                                 Vec::with_capacity(elements.len())));
+                    None
+                }
+            };
+
+            // Add the cloned value to its container, closing the containers
+            // which are complete, until there is another value to clone.
+            loop {
+                let frame = match stack.last_mut() {
+                    Some(frame) => frame,
+                    None => return value.unwrap(),
+                };
+
+                if let Some(value) = value.take() {
+                    frame.push(value);
+                }
+
+                match frame.next() {
+                    Some(next) => {
+                        source = next;
+                        break;
+                    }
+                    None => value = stack.pop().map(CloneFrame::into_value),
+                }
+            }
+        }
+    }
+}
+
+/// Compare two values if it doesn't require comparing their contents.
+fn shallow_eq(a: &JsonValue, b: &JsonValue) -> Option<bool> {
+    Some(match (a, b) {
+        (&JsonValue::String(ref a), &JsonValue::String(ref b)) => a == b,
+        (&JsonValue::Number(a), &JsonValue::Number(b)) => a == b,
+        (&JsonValue::Boolean(a), &JsonValue::Boolean(b)) => a == b,
+        (&JsonValue::Null, &JsonValue::Null) => true,
+        (&JsonValue::Invalid, &JsonValue::Invalid) => true,
+        (&JsonValue::Raw(ref a), &JsonValue::Raw(ref b)) if a.get() == b.get() => true,
+        (&JsonValue::Object(ref a), &JsonValue::Object(ref b)) if a.len() == b.len() => {
+            return None
+        }
+        (&JsonValue::Array(ref a), &JsonValue::Array(ref b)) if a.len() == b.len() => return None,
+        _ => false,
+    })
+}
+
+/// Compare two values, pushing them onto `stack` if their contents need to
+/// be compared.
+fn push_or_eq<'v, 'a>(a: &'v JsonValue<'a>,
+                      b: &'v JsonValue<'a>,
+                      stack: &mut Vec<(&'v JsonValue<'a>, &'v JsonValue<'a>)>)
+                      -> bool {
+    // Raw values are compared by content, unless they have the same text.
+    let (a, b) = match shallow_eq(a, b) {
+        Some(true) => return true,
+        _ => (a.resolved(), b.resolved()),
+    };
+
+    match shallow_eq(a, b) {
+        Some(eq) => eq,
+        None => {
+            stack.push((a, b));
+            true
+        }
+    }
+}
+
+impl<'a> PartialEq for JsonValue<'a> {
+    fn eq(&self, other: &JsonValue<'a>) -> bool {
+        // Pairs of containers of the same kind and length left to compare.
+        let mut stack = Vec::new();
+
+        if !push_or_eq(self, other, &mut stack) {
+            return false;
+        }
+
+        while let Some(pair) = stack.pop() {
+            match pair {
+                (&JsonValue::Object(ref a), &JsonValue::Object(ref b)) => {
+                    for (key, a) in a {
+                        match b.get(key) {
+                            Some(b) if push_or_eq(a, b, &mut stack) => {}
+                            _ => return false,
+                        }
+                    }
+                }
+                (&JsonValue::Array(ref a), &JsonValue::Array(ref b)) => {
+                    for (a, b) in a.iter().zip(b) {
+                        if !push_or_eq(a, b, &mut stack) {
+                            return false;
+                        }
+                    }
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        true
+    }
+}
+
+/// A container being formatted by `JsonValue::fmt`.
+struct DebugFrame<'v, 'a: 'v> {
+    children: Children<'v, 'a>,
+    close: char,
+    indent: usize,
+    empty: bool,
+    first: bool,
+}
+
+fn write_indent(f: &mut fmt::Formatter, indent: usize) -> fmt::Result {
+    for _ in 0..indent {
+        try!(f.write_str("    "));
+    }
+
+    Ok(())
+}
+
+/// Write a variant with a single field, the same way as `#[derive(Debug)]`.
+fn debug_variant<T: fmt::Debug>(f: &mut fmt::Formatter,
+                                name: &str,
+                                field: &T,
+                                indent: usize)
+                                -> fmt::Result {
+    if !f.alternate() {
+        return write!(f, "{}({:?})", name, field);
+    }
+
+    // Indent the lines of multi-line fields, such as a `RawValue`.
+    let field = format!("{:#?}", field);
+    let mut lines = field.split('\n');
+
+    try!(write!(f, "{}(\n", name));
+    try!(write_indent(f, indent + 1));
+    try!(f.write_str(lines.next().unwrap()));
+
+    for line in lines {
+        try!(f.write_str("\n"));
+        try!(write_indent(f, indent + 1));
+        try!(f.write_str(line));
+    }
+
+    try!(f.write_str(",\n"));
+    try!(write_indent(f, indent));
+    f.write_str(")")
+}
+
+impl<'a> fmt::Debug for JsonValue<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let pretty = f.alternate();
+        // The containers being formatted, innermost last.
+        let mut stack: Vec<DebugFrame> = Vec::new();
+        let mut value = self;
+        let mut indent = 0;
+
+        loop {
+            let mut complete = true;
+
+            match *value {
+                JsonValue::String(ref s) => try!(debug_variant(f, "String", s, indent)),
+                JsonValue::Number(n) => try!(debug_variant(f, "Number", &n, indent)),
+                JsonValue::Boolean(b) => try!(debug_variant(f, "Boolean", &b, indent)),
+                JsonValue::Null => try!(f.write_str("Null")),
+                JsonValue::Invalid => try!(f.write_str("Invalid")),
+                JsonValue::Raw(ref raw) => try!(debug_variant(f, "Raw", raw, indent)),
+                JsonValue::Object(_) | JsonValue::Array(_) => {
+                    let (name, open, close) = match *value {
+                        JsonValue::Object(_) => ("Object", '{', '}'),
+                        _ => ("Array", '[', ']'),
+                    };
+                    let empty = !value.has_children();
+
+                    if pretty {
+                        try!(write!(f, "{}(\n", name));
+                        try!(write_indent(f, indent + 1));
+                        try!(write!(f, "{}", open));
+
+                        if !empty {
+                            try!(f.write_str("\n"));
+                        }
+                    } else {
+                        try!(write!(f, "{}({}", name, open));
+                    }
+
+                    stack.push(DebugFrame {
+                        children: value.children(),
+                        close: close,
+                        indent: indent,
+                        empty: empty,
+                        first: true,
+                    });
+                    complete = false;
+                }
+            }
+
+            // Write the separators and closing brackets up to the next value.
+            loop {
+                let frame = match stack.last_mut() {
+                    Some(frame) => frame,
+                    None => return Ok(()),
+                };
+
+                if complete && pretty {
+                    try!(f.write_str(",\n"));
+                }
+
+                if let Some((key, child)) = frame.children.next() {
+                    if pretty {
+                        try!(write_indent(f, frame.indent + 2));
+                    } else if !frame.first {
+                        try!(f.write_str(", "));
+                    }
+
+                    if let Some(key) = key {
+                        try!(write!(f, "{:?}: ", key));
+                    }
+
+                    frame.first = false;
+                    value = child;
+                    indent = frame.indent + 2;
+                    break;
+                }
+
+                if pretty {
+                    if !frame.empty {
+                        try!(write_indent(f, frame.indent + 1));
+                    }
+
+                    try!(write!(f, "{},\n", frame.close));
+                    try!(write_indent(f, frame.indent));
+                } else {
+                    try!(write!(f, "{}", frame.close));
+                }
+
+                try!(f.write_str(")"));
+                stack.pop();
+                complete = true;
+            }
+        }
+    }
 }
@@ -1,11 +1,43 @@
+extern crate regex_lite;
+extern crate bumpalo;
+#[cfg(unix)]
+extern crate libc;
+
 mod parse_error;
-pub use parse_error::{ParseResult, ParseError};
+pub use parse_error::{ParseResult, ParseError, ParseErrorKind};
 
 mod json_value;
 pub use json_value::JsonValue;
 
 mod parser;
-pub use parser::JsonParser;
+pub use parser::{JsonParser, ParserOptions, DEFAULT_MAX_DEPTH};
+mod swar;
+
+mod interner;
+pub use interner::{Interner, InternerStats, Symbol};
+
+mod raw_value;
+pub use raw_value::RawValue;
+
+mod extract;
+pub use extract::{extract, ExtractError};
+
+mod pointer;
+pub use pointer::{PointerResult, PointerError, parse_pointer, escape_token};
+
+pub mod patch;
+pub mod merge_patch;
+pub mod json_path;
+pub mod diff;
+pub mod schema;
+pub mod structural;
+pub mod tape;
+pub mod arena;
+pub mod ndjson;
+pub mod parallel;
+#[cfg(unix)]
+pub mod mmap;
+pub mod diagnostic;
 
 pub fn parse_json(input: &str) -> ParseResult<JsonValue> {
     let parser = JsonParser::new(input);
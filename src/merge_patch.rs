@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+use JsonValue;
+
+/// Apply a JSON Merge Patch (RFC 7386) to `target`.
+///
+/// `null` members of `patch` delete the corresponding members of `target`,
+/// objects are merged recursively and any other value replaces the target.
+pub fn apply_merge_patch<'a>(target: &mut JsonValue<'a>, patch: &JsonValue<'a>) {
+    let patch_members = match *patch.resolved() {
+        JsonValue::Object(ref members) => members,
+        _ => {
+            *target = patch.clone();
+            return;
+        }
+    };
+
+    target.resolve_mut();
+
+    match *target {
+        JsonValue::Object(_) => {}
+        _ => *target = JsonValue::Object(HashMap::new()),
+    }
+
+    let target_members = match *target {
+        JsonValue::Object(ref mut members) => members,
+        _ => unreachable!(),
+    };
+
+    for (name, value) in patch_members {
+        if let JsonValue::Null = *value {
+            target_members.remove(name);
+        } else {
+            let member = target_members.entry(name.clone()).or_insert(JsonValue::Null);
+            apply_merge_patch(member, value);
+        }
+    }
+}
+
+/// Compute the smallest merge patch which turns `source` into `target`.
+///
+/// Merge patches can't set a member to `null`: such members of `target` are
+/// either left out of the patch or deleted by it.
+pub fn create_merge_patch<'a>(source: &JsonValue<'a>, target: &JsonValue<'a>) -> JsonValue<'a> {
+    let target_members = match *target.resolved() {
+        JsonValue::Object(ref members) => members,
+        _ => return target.clone(),
+    };
+
+    let source_members = match *source.resolved() {
+        JsonValue::Object(ref members) => members,
+        _ => return strip_nulls(target),
+    };
+
+    let mut patch = HashMap::new();
+
+    for name in source_members.keys() {
+        if !target_members.contains_key(name) {
+            patch.insert(name.clone(), JsonValue::Null);
+        }
+    }
+
+    for (name, target_value) in target_members {
+        match source_members.get(name) {
+            Some(source_value) if source_value == target_value => {}
+            Some(source_value) => {
+                patch.insert(name.clone(), create_merge_patch(source_value, target_value));
+            }
+            None => {
+                if let JsonValue::Null = *target_value {
+                    continue;
+                }
+                patch.insert(name.clone(), strip_nulls(target_value));
+            }
+        }
+    }
+
+    JsonValue::Object(patch)
+}
+
+/// Remove `null` object members, which would otherwise be interpreted as
+/// deletions when the value is used in a merge patch.
+fn strip_nulls<'a>(value: &JsonValue<'a>) -> JsonValue<'a> {
+    match *value.resolved() {
+        JsonValue::Object(ref members) => {
+            JsonValue::Object(members.iter()
+                                     .filter(|&(_, value)| *value != JsonValue::Null)
+                                     .map(|(name, value)| (name.clone(), strip_nulls(value)))
+                                     .collect())
+        }
+        _ => value.clone(),
+    }
+}
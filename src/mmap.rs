@@ -0,0 +1,120 @@
+//! Parsing memory-mapped files.
+//!
+//! The values parsed from a `MappedFile` borrow their strings directly from
+//! the mapping, which stays alive as long as any of them does.
+
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+use std::ptr;
+use std::slice;
+use std::str::{self, Utf8Error};
+use libc;
+use JsonValue;
+use parser::{JsonParser, ParserOptions};
+use parse_error::ParseError;
+
+#[derive(Debug)]
+pub enum MappedError {
+    /// The file isn't valid UTF-8.
+    Utf8(Utf8Error),
+    Parse(ParseError),
+}
+
+impl fmt::Display for MappedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MappedError::Utf8(ref e) => write!(f, "invalid UTF-8: {}", e),
+            MappedError::Parse(ref e) => write!(f, "invalid JSON: {}", e),
+        }
+    }
+}
+
+impl Error for MappedError {}
+
+/// A read-only memory mapping of a whole file.
+pub struct MappedFile {
+    ptr: *mut libc::c_void,
+    len: usize,
+}
+
+// The mapping is read-only, so it can be shared like a `&[u8]`.
+unsafe impl Send for MappedFile {}
+unsafe impl Sync for MappedFile {}
+
+impl MappedFile {
+    /// Map the file at `path` into memory.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated while it is mapped, since
+    /// the changes could be seen through the values borrowing from it, or
+    /// crash the process.
+    pub unsafe fn open<P: AsRef<Path>>(path: P) -> io::Result<MappedFile> {
+        let file = try!(File::open(path));
+        let len = try!(file.metadata()).len() as usize;
+
+        // Empty mappings aren't allowed.
+        if len == 0 {
+            return Ok(MappedFile {
+                ptr: ptr::null_mut(),
+                len: 0,
+            });
+        }
+
+        let ptr = libc::mmap(ptr::null_mut(),
+                             len,
+                             libc::PROT_READ,
+                             libc::MAP_PRIVATE,
+                             file.as_raw_fd(),
+                             0);
+
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+
+        // This is only a hint, so failures are ignored.
+        libc::madvise(ptr, len, libc::MADV_SEQUENTIAL);
+
+        // The mapping stays valid once the file is closed.
+        Ok(MappedFile {
+            ptr: ptr,
+            len: len,
+        })
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        if self.len == 0 {
+            return &[];
+        }
+
+        unsafe { slice::from_raw_parts(self.ptr as *const u8, self.len) }
+    }
+
+    pub fn as_str(&self) -> Result<&str, Utf8Error> {
+        str::from_utf8(self.as_bytes())
+    }
+
+    /// Parse the file. Strings without escapes borrow from the mapping.
+    pub fn parse(&self) -> Result<JsonValue, MappedError> {
+        self.parse_with_options(ParserOptions::default())
+    }
+
+    pub fn parse_with_options(&self, options: ParserOptions) -> Result<JsonValue, MappedError> {
+        let input = try!(self.as_str().map_err(MappedError::Utf8));
+        JsonParser::with_options(input, options).parse().map_err(MappedError::Parse)
+    }
+}
+
+impl Drop for MappedFile {
+    fn drop(&mut self) {
+        if self.len != 0 {
+            unsafe {
+                libc::munmap(self.ptr, self.len);
+            }
+        }
+    }
+}
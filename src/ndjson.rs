@@ -0,0 +1,231 @@
+//! Parallel parsing of newline-delimited JSON (JSON Lines).
+//!
+//! The input is split into chunks of whole lines, which are parsed on a pool
+//! of threads. Results are delivered in input order. Blank lines are
+//! skipped but still counted, so line numbers match the input.
+//!
+//! Workers only run a bounded number of chunks ahead of the oldest chunk
+//! which hasn't been delivered yet, so a slow consumer doesn't make the
+//! parsed results of the whole input pile up in memory.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex, mpsc};
+use std::thread;
+use JsonValue;
+use parser::JsonParser;
+use parse_error::ParseError;
+
+#[derive(Clone, Debug)]
+pub struct NdjsonOptions {
+    /// Number of worker threads. 0 uses the available parallelism.
+    pub threads: usize,
+    /// Approximate size of the chunks handed to the workers, in bytes.
+    pub chunk_size: usize,
+}
+
+impl Default for NdjsonOptions {
+    fn default() -> NdjsonOptions {
+        NdjsonOptions {
+            threads: 0,
+            chunk_size: 1 << 20,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct LineError {
+    /// The line of the input, starting at 1.
+    pub line: usize,
+    /// The error, with a position relative to the start of the line.
+    pub error: ParseError,
+}
+
+pub type LineResult<'a> = Result<JsonValue<'a>, LineError>;
+
+/// Parse every line of `input` in parallel.
+pub fn parse_parallel<'a>(input: &'a str, options: &NdjsonOptions) -> Vec<LineResult<'a>> {
+    let mut results = Vec::new();
+    for_each_parallel(input, options, |result| results.push(result));
+    results
+}
+
+/// Parse every line of `input` in parallel, calling `f` on the current
+/// thread with each result, in order, as soon as it and all the previous
+/// ones are available.
+pub fn for_each_parallel<'a, F>(input: &'a str, options: &NdjsonOptions, mut f: F)
+    where F: FnMut(LineResult<'a>)
+{
+    let chunks = split_chunks(input, options.chunk_size.max(1));
+    let threads = match options.threads {
+        0 => thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+        n => n,
+    };
+    let next_chunk = AtomicUsize::new(0);
+    let window = Window::new(2 * threads);
+    let (sender, receiver) = mpsc::channel();
+
+    thread::scope(|scope| {
+        for _ in 0..threads.min(chunks.len()) {
+            let sender = sender.clone();
+            let chunks = &chunks;
+            let next_chunk = &next_chunk;
+            let window = &window;
+
+            scope.spawn(move || {
+                loop {
+                    let idx = next_chunk.fetch_add(1, Ordering::Relaxed);
+
+                    if idx >= chunks.len() || !window.wait_for(idx) ||
+                       sender.send((idx, parse_chunk(chunks[idx]))).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+
+        drop(sender);
+
+        // Also wakes the workers up if `f` panics.
+        let _close = CloseOnDrop(&window);
+
+        // Chunks which arrived before some of their predecessors.
+        let mut pending = HashMap::new();
+        let mut next = 0;
+        let mut previous_lines = 0;
+
+        for (idx, chunk) in receiver {
+            pending.insert(idx, chunk);
+
+            while let Some(ParsedChunk { lines, results }) = pending.remove(&next) {
+                for result in results {
+                    f(result.map_err(|mut e| {
+                        e.line += previous_lines;
+                        e
+                    }));
+                }
+
+                previous_lines += lines;
+                next += 1;
+                window.advance(next);
+            }
+        }
+    });
+}
+
+/// The chunks which may be parsed: the workers wait until the consumer is
+/// less than `size` chunks behind.
+struct Window {
+    /// The index of the first chunk which may not be parsed yet, or `None`
+    /// once the consumer is gone.
+    end: Mutex<Option<usize>>,
+    advanced: Condvar,
+    size: usize,
+}
+
+impl Window {
+    fn new(size: usize) -> Window {
+        Window {
+            end: Mutex::new(Some(size)),
+            advanced: Condvar::new(),
+            size: size,
+        }
+    }
+
+    /// Block until the chunk at `idx` may be parsed. Returns `false` if the
+    /// consumer is gone.
+    fn wait_for(&self, idx: usize) -> bool {
+        let mut end = self.end.lock().unwrap_or_else(|e| e.into_inner());
+
+        loop {
+            match *end {
+                Some(limit) if idx >= limit => {
+                    end = self.advanced.wait(end).unwrap_or_else(|e| e.into_inner());
+                }
+                Some(_) => return true,
+                None => return false,
+            }
+        }
+    }
+
+    /// Record that the chunks before `next` have been delivered.
+    fn advance(&self, next: usize) {
+        *self.end.lock().unwrap_or_else(|e| e.into_inner()) = Some(next + self.size);
+        self.advanced.notify_all();
+    }
+
+    fn close(&self) {
+        *self.end.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        self.advanced.notify_all();
+    }
+}
+
+struct CloseOnDrop<'w>(&'w Window);
+
+impl<'w> Drop for CloseOnDrop<'w> {
+    fn drop(&mut self) {
+        self.0.close();
+    }
+}
+
+/// Split `input` after the first newline following every `chunk_size` bytes.
+fn split_chunks(input: &str, chunk_size: usize) -> Vec<&str> {
+    let mut chunks = Vec::with_capacity(input.len() / chunk_size + 1);
+    let mut rest = input;
+
+    while !rest.is_empty() {
+        let end = match rest.as_bytes().iter().skip(chunk_size).position(|&b| b == b'\n') {
+            Some(newline) => chunk_size + newline + 1,
+            None => rest.len(),
+        };
+
+        chunks.push(&rest[..end]);
+        rest = &rest[end..];
+    }
+
+    chunks
+}
+
+struct ParsedChunk<'a> {
+    lines: usize,
+    /// Errors have line numbers relative to the start of the chunk.
+    results: Vec<LineResult<'a>>,
+}
+
+fn parse_chunk(chunk: &str) -> ParsedChunk {
+    let mut lines = 0;
+    let mut results = Vec::new();
+
+    for line in chunk.split_terminator('\n') {
+        lines += 1;
+
+        if line.trim_matches(&[' ', '\t', '\r'][..]).is_empty() {
+            continue;
+        }
+
+        let parser = JsonParser::new(line);
+        parser.expect_optional_whitespace();
+
+        let result = parser.parse().and_then(|value| {
+            // Only one value per line, e.g. `{"a":1} {"b":2}` is an error.
+            parser.expect_optional_whitespace();
+
+            match parser.current_byte() {
+                Some(_) => Err(parser.unexpected_character(vec!['\n'])),
+                None => Ok(value),
+            }
+        });
+
+        results.push(result.map_err(|e| {
+            LineError {
+                line: lines,
+                error: e,
+            }
+        }));
+    }
+
+    ParsedChunk {
+        lines: lines,
+        results: results,
+    }
+}
@@ -0,0 +1,202 @@
+//! Parallel parsing of a document made of one large array.
+//!
+//! The input is first scanned for the boundaries of the top-level elements,
+//! keeping track of strings and escapes so that brackets and commas inside
+//! strings are ignored. Groups of elements are then parsed concurrently.
+//! Whenever this could give a different result than `JsonParser::parse`,
+//! the document is parsed sequentially instead.
+
+use std::thread;
+use JsonValue;
+use parser::{JsonParser, ParserOptions, exceeds};
+use parse_error::ParseResult;
+use swar;
+
+#[derive(Clone, Debug)]
+pub struct ParallelOptions {
+    /// Number of threads. 0 uses the available parallelism.
+    pub threads: usize,
+    /// Documents smaller than this many bytes are parsed sequentially.
+    pub min_size: usize,
+    /// The options of every parser. The limits apply to the whole document:
+    /// `max_nodes` counts the values parsed by all the threads.
+    pub parser: ParserOptions,
+}
+
+impl Default for ParallelOptions {
+    fn default() -> ParallelOptions {
+        ParallelOptions {
+            threads: 0,
+            min_size: 1 << 20,
+            parser: ParserOptions::default(),
+        }
+    }
+}
+
+/// Parse `input`, parsing the elements of a top-level array in parallel.
+///
+/// The result, including errors, is the same as with `JsonParser::parse`
+/// and `options.parser`.
+pub fn parse_parallel<'a>(input: &'a str, options: &ParallelOptions) -> ParseResult<JsonValue<'a>> {
+    let parser_options = &options.parser;
+    let sequential = || JsonParser::with_options(input, parser_options.clone()).parse();
+    let threads = match options.threads {
+        0 => thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+        n => n,
+    };
+
+    // The workers only see the elements, so anything which is decided at
+    // the root is left to the sequential parser.
+    let root_options = exceeds(input.len(), parser_options.max_input_len) ||
+                       parser_options.max_depth == 0 ||
+                       parser_options.lazy_depth == Some(0);
+
+    let bounds = if input.len() >= options.min_size && threads > 1 && !root_options {
+        element_bounds(input.as_bytes())
+    } else {
+        None
+    };
+
+    let bounds = match bounds {
+        Some(ref bounds) if exceeds(bounds.len(), parser_options.max_container_len) => None,
+        bounds => bounds,
+    };
+
+    let bounds = match bounds {
+        Some(bounds) => bounds,
+        None => return sequential(),
+    };
+
+    let groups: Vec<&[(usize, usize)]> = bounds.chunks((bounds.len() / (threads * 4)).max(1))
+                                               .collect();
+
+    // Worker `i` parses the groups `i`, `i + threads`, `i + 2 * threads`...
+    let parsed: Vec<ParseResult<(Vec<JsonValue<'a>>, usize)>> = thread::scope(|scope| {
+        let groups = &groups;
+        let workers: Vec<_> = (0..threads)
+                                  .map(|worker| {
+                                      scope.spawn(move || {
+                                          groups.iter()
+                                                .skip(worker)
+                                                .step_by(threads)
+                                                .map(|group| parse_elements(input, parser_options, group))
+                                                .collect::<Vec<_>>()
+                                      })
+                                  })
+                                  .collect();
+
+        let mut results: Vec<_> = workers.into_iter()
+                                         .map(|worker| worker.join().unwrap().into_iter())
+                                         .collect();
+
+        (0..groups.len()).map(|i| results[i % threads].next().unwrap()).collect()
+    });
+
+    let mut elements = Vec::with_capacity(bounds.len());
+    // The root array is a node too.
+    let mut nodes = 1;
+
+    for group in parsed {
+        match group {
+            Ok((values, group_nodes)) => {
+                elements.extend(values);
+                nodes += group_nodes;
+            }
+            // Let the sequential parser report the first error.
+            Err(_) => return sequential(),
+        }
+    }
+
+    if exceeds(nodes, parser_options.max_nodes) {
+        return sequential();
+    }
+
+    Ok(JsonValue::Array(elements))
+}
+
+/// Parse the elements at `bounds`, returning them with the number of
+/// values parsed.
+fn parse_elements<'a>(input: &'a str,
+                      options: &ParserOptions,
+                      bounds: &[(usize, usize)])
+                      -> ParseResult<(Vec<JsonValue<'a>>, usize)> {
+    let parser = JsonParser::with_options(input, options.clone());
+    let mut values = Vec::with_capacity(bounds.len());
+
+    for &(start, end) in bounds {
+        parser.resume_at(start, 1);
+        parser.expect_optional_whitespace();
+        values.push(try!(parser.parse_value()));
+        parser.expect_optional_whitespace();
+
+        if parser.current_idx() != end {
+            return Err(parser.unexpected_character(vec![',', ']']));
+        }
+    }
+
+    Ok((values, parser.nodes()))
+}
+
+fn is_blank(bytes: &[u8]) -> bool {
+    swar::whitespace_len(bytes) == bytes.len()
+}
+
+/// The span of each element of the top-level array, from the byte after
+/// the preceding `[` or `,` to the following `,` or `]`. Returns `None` if
+/// `bytes` doesn't look like a well-formed array.
+fn element_bounds(bytes: &[u8]) -> Option<Vec<(usize, usize)>> {
+    if bytes.first() != Some(&b'[') {
+        return None;
+    }
+
+    let mut bounds = Vec::new();
+    let mut depth = 0;
+    let mut start = 1;
+    let mut idx = 1;
+
+    loop {
+        match bytes.get(idx) {
+            Some(&b'"') => {
+                loop {
+                    idx += 1;
+                    idx += match bytes.get(idx..) {
+                        Some(rest) => swar::find_string_special(rest),
+                        None => return None,
+                    };
+
+                    match bytes.get(idx) {
+                        Some(&b'"') => break,
+                        Some(&b'\\') => idx += 1,
+                        Some(_) => {}
+                        None => return None,
+                    }
+                }
+            }
+            Some(&b'[') | Some(&b'{') => depth += 1,
+            Some(&b']') | Some(&b'}') if depth > 0 => depth -= 1,
+            Some(&b']') => {
+                bounds.push((start, idx));
+                break;
+            }
+            Some(&b',') if depth == 0 => {
+                bounds.push((start, idx));
+                start = idx + 1;
+            }
+            Some(&b'}') | None => return None,
+            Some(_) => {}
+        }
+
+        idx += 1;
+    }
+
+    // `[]`, or a trailing comma, which the sequential parser accepts.
+    if bounds.last().is_some_and(|&(start, end)| is_blank(&bytes[start..end])) {
+        bounds.pop();
+    }
+
+    if bounds.iter().any(|&(start, end)| is_blank(&bytes[start..end])) {
+        return None;
+    }
+
+    Some(bounds)
+}
@@ -1,55 +1,177 @@
+use std::error::Error;
+use std::fmt;
+
 pub type ParseResult<T> = Result<T, ParseError>;
 
 #[derive(Debug)]
 struct ParseErrorContext {
-    pub line: usize,
-    pub column: usize,
+    line: usize,
+    column: usize,
+    byte_column: usize,
+    utf16_column: usize,
 }
 
 impl ParseErrorContext {
     pub fn new(input: &str, idx: usize) -> ParseErrorContext {
-        // Iterate over `input` to compute the line and column lazily.
-
-        let mut line = 1;
-        let mut column = 1;
-
-        for (i, c) in input.chars().enumerate() {
-            if i == idx {
-                break;
-            }
+        // Only the line of the error needs to be decoded, the lines before it
+        // are just counted.
+        let idx = idx.min(input.len());
+        let before = &input.as_bytes()[..idx];
+        let line_start = before.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+        let line = before[..line_start].iter().filter(|&&b| b == b'\n').count() + 1;
 
-            if c == '\n' {
-                line += 1;
-                column = 1;
-            } else {
-                column += 1;
-            }
+        // Limits can be exceeded in the middle of a character, the column is
+        // then the one of that character.
+        let mut end = idx;
+        while !input.is_char_boundary(end) {
+            end -= 1;
         }
 
+        let prefix = &input[line_start..end];
+
         ParseErrorContext {
             line: line,
-            column: column,
+            column: prefix.chars().count() + 1,
+            byte_column: idx - line_start + 1,
+            utf16_column: prefix.encode_utf16().count() + 1,
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(PartialEq, Clone, Debug)]
+#[non_exhaustive]
 pub enum ParseErrorKind {
     UnexpectedEndOfInput,
+    /// The character found, and the characters which would have been valid.
     UnexpectedCharacter(char, Vec<char>),
+    /// Objects and arrays are nested deeper than `ParserOptions::max_depth`.
+    DepthLimitExceeded,
+    /// The input is longer than `ParserOptions::max_input_len`, or too long
+    /// to be parsed into a `Tape`.
+    InputTooLarge,
+    /// A string is longer than `ParserOptions::max_string_len`.
+    StringTooLong,
+    /// A container has more items than `ParserOptions::max_container_len`.
+    TooManyElements,
+    /// The document has more values than `ParserOptions::max_nodes`.
+    TooManyNodes,
+    /// A number is longer than `ParserOptions::max_number_len`.
+    NumberTooLong,
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseErrorKind::UnexpectedEndOfInput => f.write_str("unexpected end of input"),
+            ParseErrorKind::UnexpectedCharacter(_, ref expected) if expected.is_empty() => {
+                f.write_str("unexpected character")
+            }
+            ParseErrorKind::UnexpectedCharacter(_, ref expected) => {
+                try!(f.write_str("expected "));
+
+                for (i, c) in expected.iter().enumerate() {
+                    if i + 1 == expected.len() && i > 0 {
+                        try!(f.write_str(" or "));
+                    } else if i > 0 {
+                        try!(f.write_str(", "));
+                    }
+
+                    try!(write!(f, "{:?}", c));
+                }
+
+                Ok(())
+            }
+            ParseErrorKind::DepthLimitExceeded => f.write_str("nesting too deep"),
+            ParseErrorKind::InputTooLarge => f.write_str("input too large"),
+            ParseErrorKind::StringTooLong => f.write_str("string too long"),
+            ParseErrorKind::TooManyElements => f.write_str("too many elements"),
+            ParseErrorKind::TooManyNodes => f.write_str("too many values"),
+            ParseErrorKind::NumberTooLong => f.write_str("number too long"),
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct ParseError {
+    offset: usize,
     ctx: ParseErrorContext,
     kind: ParseErrorKind,
+    pointer: Option<String>,
 }
 
 impl ParseError {
     pub fn new(input: &str, idx: usize, kind: ParseErrorKind) -> ParseError {
         ParseError {
+            offset: idx,
             ctx: ParseErrorContext::new(input, idx),
             kind: kind,
+            pointer: None,
+        }
+    }
+
+    pub fn kind(&self) -> &ParseErrorKind {
+        &self.kind
+    }
+
+    /// The position of the error in the input, in bytes.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The line of the error, starting at 1.
+    pub fn line(&self) -> usize {
+        self.ctx.line
+    }
+
+    /// The column of the error in Unicode scalar values, starting at 1.
+    pub fn column(&self) -> usize {
+        self.ctx.column
+    }
+
+    /// The column of the error in bytes, starting at 1.
+    pub fn byte_column(&self) -> usize {
+        self.ctx.byte_column
+    }
+
+    /// The column of the error in UTF-16 code units, starting at 1, as used
+    /// by the Language Server Protocol.
+    pub fn utf16_column(&self) -> usize {
+        self.ctx.utf16_column
+    }
+
+    /// The JSON Pointer of the value being parsed when the error happened,
+    /// such as `/friends/2/name`. Errors in separators or keys point to the
+    /// object or array containing them, and errors at the root to `""`.
+    ///
+    /// Only `JsonParser` keeps track of the pointer: it is `None` for the
+    /// errors of `structural::parse`, `Tape::parse`, `arena::parse_in` and
+    /// `extract`. Errors inside a lazy `Raw` value point to the raw value.
+    pub fn pointer(&self) -> Option<&str> {
+        self.pointer.as_ref().map(|pointer| &pointer[..])
+    }
+
+    pub(crate) fn set_pointer(&mut self, pointer: Option<String>) {
+        self.pointer = pointer;
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(f, "{} at ", self.kind));
+
+        match self.pointer() {
+            Some(pointer) if !pointer.is_empty() => try!(write!(f, "{}, ", pointer)),
+            _ => {}
         }
+
+        try!(write!(f, "line {}, column {}", self.line(), self.column()));
+
+        if let ParseErrorKind::UnexpectedCharacter(found, _) = self.kind {
+            try!(write!(f, ", found {:?}", found));
+        }
+
+        Ok(())
     }
 }
+
+impl Error for ParseError {}
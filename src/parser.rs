@@ -1,29 +1,164 @@
 use std::collections::HashMap;
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
 use std::borrow::Cow;
+use std::mem;
 use std::str;
 use JsonValue;
+use RawValue;
+use Interner;
+use escape_token;
 use parse_error::*;
+use swar;
+
+/// The default for `ParserOptions::max_depth`.
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+/// Options controlling how a `JsonParser` builds its output.
+#[derive(Clone, Debug)]
+pub struct ParserOptions {
+    /// Objects and arrays nested at least this deep are validated but kept
+    /// as `JsonValue::Raw` slices of the input instead of being parsed. The
+    /// root value is at depth 0, so `Some(1)` defers every nested container.
+    ///
+    /// Containers are only chosen by depth: there is no way to keep some
+    /// members raw and parse others at the same depth.
+    pub lazy_depth: Option<usize>,
+    /// The maximum number of nested objects and arrays. Deeper input is
+    /// rejected with `ParseErrorKind::DepthLimitExceeded` rather than
+    /// risking a stack overflow.
+    pub max_depth: usize,
+
+    // Limits for untrusted input. Each is checked before the memory it
+    // guards is allocated, and is unlimited by default.
+
+    /// The maximum length of the input, in bytes.
+    pub max_input_len: Option<usize>,
+    /// The maximum length of a string or key, in bytes of input (escapes
+    /// count for two bytes).
+    pub max_string_len: Option<usize>,
+    /// The maximum number of members of an object or elements of an array.
+    pub max_container_len: Option<usize>,
+    /// The maximum number of values in the document, containers included.
+    pub max_nodes: Option<usize>,
+    /// The maximum length of a number, in bytes.
+    pub max_number_len: Option<usize>,
+}
+
+impl Default for ParserOptions {
+    fn default() -> ParserOptions {
+        ParserOptions {
+            lazy_depth: None,
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_input_len: None,
+            max_string_len: None,
+            max_container_len: None,
+            max_nodes: None,
+            max_number_len: None,
+        }
+    }
+}
+
+pub(crate) fn exceeds(len: usize, limit: Option<usize>) -> bool {
+    limit.is_some_and(|limit| len > limit)
+}
 
 pub struct JsonParser<'input> {
     input: &'input str,
     current_idx: Cell<usize>,
+    /// Number of objects and arrays currently being parsed.
+    depth: Cell<usize>,
+    /// Number of values parsed so far.
+    nodes: Cell<usize>,
+    options: ParserOptions,
+    /// Object keys are interned here if set.
+    interner: Option<&'input Interner>,
+    /// Buffer for unescaping keys before interning them.
+    key_buf: RefCell<Vec<u8>>,
+    /// Whether errors are collected in `errors` instead of being returned,
+    /// see `parse_recovering`.
+    recovering: Cell<bool>,
+    errors: RefCell<Vec<ParseError>>,
 }
 
 // Public interface
 impl<'input> JsonParser<'input> {
     /// Create a new `JsonParser` with the given input.
     pub fn new(input: &str) -> JsonParser {
+        JsonParser::with_options(input, ParserOptions::default())
+    }
+
+    /// Create a new `JsonParser` with the given input and options.
+    pub fn with_options(input: &str, options: ParserOptions) -> JsonParser {
         JsonParser {
             input: input,
             current_idx: Cell::new(0),
+            depth: Cell::new(0),
+            nodes: Cell::new(0),
+            options: options,
+            interner: None,
+            key_buf: RefCell::new(Vec::new()),
+            recovering: Cell::new(false),
+            errors: RefCell::new(Vec::new()),
         }
     }
 
+    /// Create a new `JsonParser` which interns object keys in `interner`.
+    ///
+    /// The keys of the parsed objects then borrow from the interner, which
+    /// can be shared by several parsers.
+    pub fn with_interner(input: &'input str,
+                         options: ParserOptions,
+                         interner: &'input Interner)
+                         -> JsonParser<'input> {
+        let mut parser = JsonParser::with_options(input, options);
+        parser.interner = Some(interner);
+        parser
+    }
+
     /// Parse the input as an array or an object.
     pub fn parse(&self) -> ParseResult<JsonValue<'input>> {
+        if let Some(max) = self.options.max_input_len.filter(|&max| self.input.len() > max) {
+            let mut error = ParseError::new(self.input, max, ParseErrorKind::InputTooLarge);
+            error.set_pointer(Some(String::new()));
+            return Err(error);
+        }
+
         Ok(try!(self.parse_value()))
     }
+
+    /// Parse the input, recovering from errors instead of stopping at the
+    /// first one. Returns the best-effort value, where the items which
+    /// couldn't be parsed are `JsonValue::Invalid`, and all the errors.
+    ///
+    /// After an error, parsing resumes at the next comma or closing bracket
+    /// of the container being parsed. Unlike with `parse`, input after the
+    /// value is reported, since an error may have closed it too early.
+    pub fn parse_recovering(&self) -> (JsonValue<'input>, Vec<ParseError>) {
+        self.recovering.set(true);
+        let result = self.parse();
+        self.recovering.set(false);
+
+        let mut errors = self.errors.replace(Vec::new());
+
+        match result {
+            Ok(value) => {
+                self.expect_optional_whitespace();
+
+                // An invalid root value wasn't skipped.
+                if value != JsonValue::Invalid && self.current_byte().is_some() {
+                    let mut error = self.unexpected_character(vec![]);
+                    error.set_pointer(Some(String::new()));
+                    errors.push(error);
+                }
+
+                (value, errors)
+            }
+            Err(error) => {
+                errors.push(error);
+                (JsonValue::Invalid, errors)
+            }
+        }
+    }
 }
 
 // Utility functions
@@ -48,7 +183,7 @@ fn char_at(s: &str, i: usize) -> Option<char> {
 
 // Private methods
 impl<'input> JsonParser<'input> {
-    fn current_byte(&self) -> Option<u8> {
+    pub(crate) fn current_byte(&self) -> Option<u8> {
         if self.current_idx() < self.input.len() {
             Some(self.input.as_bytes()[self.current_idx()])
         } else {
@@ -60,18 +195,32 @@ impl<'input> JsonParser<'input> {
         char_at(self.input, self.current_idx())
     }
 
-    fn current_idx(&self) -> usize {
+    pub(crate) fn current_idx(&self) -> usize {
         self.current_idx.get()
     }
 
+    /// The number of values parsed so far.
+    pub(crate) fn nodes(&self) -> usize {
+        self.nodes.get()
+    }
+
+    /// Continue parsing at `idx`, as if `depth` containers were open.
+    pub(crate) fn resume_at(&self, idx: usize, depth: usize) {
+        self.current_idx.set(idx);
+        self.depth.set(depth);
+    }
+
     fn next(&self, n: usize) {
         let new_idx = self.current_idx() + n;
 
         self.current_idx.set(new_idx);
     }
 
-    fn expect(&self, expected: char) -> ParseResult<()> {
-        let found = self.current_char().unwrap();
+    pub(crate) fn expect(&self, expected: char) -> ParseResult<()> {
+        let found = match self.current_char() {
+            Some(c) => c,
+            None => return Err(self.unexpected_end_of_input()),
+        };
 
         if found != expected {
             return Err(ParseError::new(self.input,
@@ -83,129 +232,353 @@ impl<'input> JsonParser<'input> {
         Ok(())
     }
 
-    fn expect_optional_whitespace(&self) {
-        while let Some(b) = self.current_byte() {
-            if !is_whitespace(b) {
-                break;
-            }
-            self.next(1);
+    pub(crate) fn expect_optional_whitespace(&self) {
+        // Most runs are a single space or none at all.
+        match self.current_byte() {
+            Some(b) if is_whitespace(b) => self.next(1),
+            _ => return,
+        }
+
+        let rest = &self.input.as_bytes()[self.current_idx()..];
+        self.next(swar::whitespace_len(rest));
+    }
+
+    /// At the start of an item or at the closing bracket of the innermost
+    /// container of `stack`: either close it and return its value, or get
+    /// ready to parse the next item, including the key of object members.
+    fn next_item(&self, stack: &mut Vec<Frame<'input>>) -> ParseResult<Option<JsonValue<'input>>> {
+        let close = stack.last().unwrap().close();
+
+        if self.current_byte() == Some(close) {
+            try!(self.expect(close as char));
+            self.leave();
+            return Ok(Some(stack.pop().unwrap().into_value()));
+        }
+
+        let frame = stack.last_mut().unwrap();
+        try!(self.check_container_len(frame.len()));
+
+        if let Frame::Object(_, ref mut key) = *frame {
+            *key = try!(self.parse_member_key());
         }
+
+        Ok(None)
     }
 
-    fn parse_object(&self) -> ParseResult<HashMap<Cow<'input, str>, JsonValue<'input>>> {
-        let mut output = HashMap::new();
+    fn parse_member_key(&self) -> ParseResult<Cow<'input, str>> {
+        let key = match self.interner {
+            Some(interner) => Cow::Borrowed(try!(self.parse_interned_key(interner))),
+            None => try!(self.parse_string()),
+        };
 
         self.expect_optional_whitespace();
-        try!(self.expect('{'));
+        try!(self.expect(':'));
         self.expect_optional_whitespace();
 
-        loop {
+        Ok(key)
+    }
 
-            let (property, value) = try!(self.parse_key_value_pair());
-            output.insert(property, value);
+    fn parse_interned_key(&self, interner: &'input Interner) -> ParseResult<&'input str> {
+        let mut buf = self.key_buf.borrow_mut();
+        buf.clear();
 
-            self.expect_optional_whitespace();
+        Ok(match try!(self.parse_string_into(&mut *buf)) {
+            Some(key) => interner.intern_str(key),
+            // See `parse_string`.
+            None => interner.intern_str(unsafe { str::from_utf8_unchecked(&buf) }),
+        })
+    }
 
-            match self.current_byte().unwrap() {
-                b',' => {
-                    try!(self.expect(','));
-                    self.expect_optional_whitespace();
+    fn limit_exceeded(&self, idx: usize, kind: ParseErrorKind) -> ParseError {
+        ParseError::new(self.input, idx, kind)
+    }
 
-                    match self.current_byte().unwrap() {
-                        b'}' => {
-                            try!(self.expect('}'));
-                            break;
-                        }
-                        _ => {
-                            continue;
+    /// Check that a container with `len` items can get another one.
+    fn check_container_len(&self, len: usize) -> ParseResult<()> {
+        if exceeds(len + 1, self.options.max_container_len) {
+            return Err(self.limit_exceeded(self.current_idx(), ParseErrorKind::TooManyElements));
+        }
+
+        Ok(())
+    }
+
+    /// Enter an object or an array, checking the depth limit.
+    pub(crate) fn enter(&self) -> ParseResult<()> {
+        if self.depth.get() >= self.options.max_depth {
+            return Err(ParseError::new(self.input,
+                                       self.current_idx(),
+                                       ParseErrorKind::DepthLimitExceeded));
+        }
+
+        self.depth.set(self.depth.get() + 1);
+        Ok(())
+    }
+
+    pub(crate) fn leave(&self) {
+        self.depth.set(self.depth.get() - 1);
+    }
+
+    fn is_lazy(&self) -> bool {
+        self.options.lazy_depth.is_some_and(|lazy_depth| self.depth.get() >= lazy_depth)
+    }
+
+    /// Parse a value and everything nested in it. Containers are kept on a
+    /// heap-allocated stack, so the native stack usage doesn't depend on the
+    /// nesting depth.
+    pub(crate) fn parse_value(&self) -> ParseResult<JsonValue<'input>> {
+        // The objects and arrays being parsed, innermost last.
+        let mut stack = Vec::new();
+
+        'values: loop {
+            let start = self.current_idx();
+
+            let mut value = match self.start_value() {
+                Ok(Start::Value(value)) => value,
+                Ok(Start::Container(frame)) => {
+                    stack.push(frame);
+                    let start = self.current_idx();
+
+                    match self.next_item(&mut stack) {
+                        Ok(Some(empty)) => empty,
+                        Ok(None) => continue 'values,
+                        Err(error) => {
+                            match try!(self.recover(error, &mut stack, start, false)) {
+                                Some(value) => value,
+                                None => continue 'values,
+                            }
                         }
                     }
                 }
-                b'}' => {
-                    try!(self.expect('}'));
-                    break;
+                Err(error) => {
+                    match try!(self.recover(error, &mut stack, start, true)) {
+                        Some(value) => value,
+                        None => continue 'values,
+                    }
                 }
-                _ => {
-                    return Err(
-            ParseError::new(
-              self.input,
-              self.current_idx(),
-              ParseErrorKind::UnexpectedCharacter(self.current_char().unwrap(), vec![',', '}'])
-            )
-          );
+            };
+
+            // Add the complete value to its container, then close every
+            // container that it completes.
+            loop {
+                let close = match stack.last_mut() {
+                    Some(frame) => {
+                        frame.push(value);
+                        frame.close()
+                    }
+                    None => return Ok(value),
+                };
+
+                self.expect_optional_whitespace();
+                let start = self.current_idx();
+
+                let next = match self.current_byte() {
+                    Some(b',') => {
+                        try!(self.expect(','));
+                        self.expect_optional_whitespace();
+                        self.next_item(&mut stack)
+                    }
+                    Some(b) if b == close => self.next_item(&mut stack),
+                    _ => Err(self.unexpected_character(vec![',', close as char])),
+                };
+
+                let next = match next {
+                    Ok(next) => next,
+                    Err(error) => try!(self.recover(error, &mut stack, start, false)),
+                };
+
+                match next {
+                    Some(container) => value = container,
+                    None => continue 'values,
                 }
             }
         }
+    }
 
-        self.expect_optional_whitespace();
+    /// Handle an error in an item which started at `start`. The JSON Pointer
+    /// of the error is set to the item if `placeholder` is set, or else to
+    /// its container. Unless parsing is `recovering`, the error is returned.
+    ///
+    /// Otherwise, the error is collected and the item is replaced with a
+    /// placeholder if `placeholder` is set. The rest of the item is then
+    /// skipped, up to the next comma or closing bracket, and the result is
+    /// the same as `next_item`. The end of the input closes all the
+    /// containers.
+    fn recover(&self,
+               mut error: ParseError,
+               stack: &mut Vec<Frame<'input>>,
+               start: usize,
+               placeholder: bool)
+               -> ParseResult<Option<JsonValue<'input>>> {
+        error.set_pointer(Some(pointer(stack, placeholder)));
+
+        if !self.recovering.get() {
+            return Err(error);
+        }
 
-        Ok(output)
-    }
+        self.errors.borrow_mut().push(error);
 
-    fn parse_array(&self) -> ParseResult<Vec<JsonValue<'input>>> {
-        let mut output = Vec::with_capacity(2);
+        if placeholder {
+            match stack.last_mut() {
+                Some(frame) => frame.push(JsonValue::Invalid),
+                None => return Ok(Some(JsonValue::Invalid)),
+            }
+        }
 
-        self.expect_optional_whitespace();
-        try!(self.expect('['));
-        self.expect_optional_whitespace();
+        self.skip_failed_item(start);
 
         loop {
-            let value = try!(self.parse_value());
-            output.push(value);
+            self.skip_garbage();
+            let close = stack.last().unwrap().close();
 
-            self.expect_optional_whitespace();
-
-            match self.current_byte().unwrap() {
-                b',' => {
-                    try!(self.expect(','));
+            match self.current_byte() {
+                Some(b',') => {
+                    self.next(1);
                     self.expect_optional_whitespace();
-
-                    match self.current_byte().unwrap() {
-                        b']' => {
-                            try!(self.expect(']'));
-                            break;
+                    let start = self.current_idx();
+
+                    match self.next_item(stack) {
+                        Ok(next) => return Ok(next),
+                        Err(mut error) => {
+                            error.set_pointer(Some(pointer(stack, false)));
+                            self.errors.borrow_mut().push(error);
+                            self.skip_failed_item(start);
                         }
-                        _ => {
-                            continue;
+                    }
+                }
+                Some(b) if b == close => return self.next_item(stack),
+                Some(b) => {
+                    // Brackets which were left open are closed, and stray
+                    // ones are skipped.
+                    match stack.iter().rposition(|frame| frame.close() == b) {
+                        Some(matching) => {
+                            self.close_frames(stack, matching + 1);
+                            return self.next_item(stack);
                         }
+                        None => self.next(1),
                     }
                 }
-                b']' => {
-                    try!(self.expect(']'));
-                    break;
-                }
-                _ => {
-                    return Err(
-            ParseError::new(
-              self.input,
-              self.current_idx(),
-              ParseErrorKind::UnexpectedCharacter(self.current_char().unwrap(), vec![',', ']'])
-            )
-          );
+                None => {
+                    self.close_frames(stack, 1);
+                    self.leave();
+                    return Ok(stack.pop().map(Frame::into_value));
                 }
             }
         }
+    }
+
+    /// Close the containers of `stack` past the first `len` ones, adding
+    /// them to their parent.
+    fn close_frames(&self, stack: &mut Vec<Frame<'input>>, len: usize) {
+        while stack.len() > len {
+            let value = stack.pop().unwrap().into_value();
+            self.leave();
+            stack.last_mut().unwrap().push(value);
+        }
+    }
+
+    /// If the error is in the middle of a string starting at `start`, skip
+    /// the string so that its content isn't taken for brackets. Unterminated
+    /// strings end at the end of the line.
+    ///
+    /// If it is in the middle of a lazy container, go back to its opening
+    /// bracket, so that `skip_garbage` skips the container as a whole.
+    fn skip_failed_item(&self, start: usize) {
+        if self.current_idx() <= start {
+            return;
+        }
+
+        match self.input.as_bytes().get(start) {
+            Some(&b'"') => {
+                self.current_idx.set(start + 1);
+                self.skip_string_content();
+            }
+            Some(&b'{') | Some(&b'[') => self.current_idx.set(start),
+            _ => {}
+        }
+    }
 
-        Ok(output)
+    /// Skip to the end of the string the parser is in, or to the end of the
+    /// line if it isn't terminated.
+    fn skip_string_content(&self) {
+        while let Some(b) = self.current_byte() {
+            match b {
+                b'"' => return self.next(1),
+                b'\n' => return,
+                b'\\' => self.next(2.min(self.input.len() - self.current_idx())),
+                _ => self.next(1),
+            }
+        }
     }
 
-    fn parse_key_value_pair(&self) -> ParseResult<(Cow<'input, str>, JsonValue<'input>)> {
-        let property_name = try!(self.parse_string());
+    /// Skip to the next comma or closing bracket which isn't nested in
+    /// brackets or in a string.
+    fn skip_garbage(&self) {
+        let mut depth = 0;
 
-        self.expect_optional_whitespace();
-        try!(self.expect(':'));
-        self.expect_optional_whitespace();
-        let value = try!(self.parse_value());
-        self.expect_optional_whitespace();
+        while let Some(b) = self.current_byte() {
+            match b {
+                b',' | b'}' | b']' if depth == 0 => return,
+                b'{' | b'[' => depth += 1,
+                b'}' | b']' => depth -= 1,
+                b'"' => {
+                    self.next(1);
+                    self.skip_string_content();
+                    continue;
+                }
+                _ => {}
+            }
 
-        Ok((property_name, value))
+            self.next(1);
+        }
     }
 
-    fn parse_value(&self) -> ParseResult<JsonValue<'input>> {
-        Ok(match self.current_byte().unwrap() {
+    /// Parse a scalar or a lazy container, or open an object or an array.
+    fn start_value(&self) -> ParseResult<Start<'input>> {
+        self.nodes.set(self.nodes.get() + 1);
+
+        if exceeds(self.nodes.get(), self.options.max_nodes) {
+            return Err(self.limit_exceeded(self.current_idx(), ParseErrorKind::TooManyNodes));
+        }
+
+        if self.is_lazy() && (self.current_byte() == Some(b'{') || self.current_byte() == Some(b'[')) {
+            let start = self.current_idx();
+
+            match self.parse_raw_value() {
+                Ok(raw) => return Ok(Start::Value(JsonValue::Raw(raw))),
+                // Recovery needs the containers on the stack, so the
+                // container is parsed again like without `lazy_depth`, to
+                // report the same errors.
+                Err(_) if self.recovering.get() => self.current_idx.set(start),
+                Err(error) => return Err(error),
+            }
+        }
+
+        match self.current_byte() {
+            Some(b'{') => {
+                try!(self.enter());
+                try!(self.expect('{'));
+                self.expect_optional_whitespace();
+                Ok(Start::Container(Frame::Object(HashMap::new(), Cow::Borrowed(""))))
+            }
+            Some(b'[') => {
+                try!(self.enter());
+                try!(self.expect('['));
+                self.expect_optional_whitespace();
+                Ok(Start::Container(Frame::Array(Vec::with_capacity(2))))
+            }
+            _ => self.parse_scalar().map(Start::Value),
+        }
+    }
+
+    /// Parse a value which isn't an object or an array.
+    pub(crate) fn parse_scalar(&self) -> ParseResult<JsonValue<'input>> {
+        let b = match self.current_byte() {
+            Some(b) => b,
+            None => return Err(self.unexpected_end_of_input()),
+        };
+
+        Ok(match b {
             b'"' => JsonValue::String(try!(self.parse_string())),
-            b'{' => JsonValue::Object(try!(self.parse_object())),
-            b'[' => JsonValue::Array(try!(self.parse_array())),
             b'0'...b'9' | b'-' => JsonValue::Number(try!(self.parse_number())),
             b't' | b'f' => JsonValue::Boolean(try!(self.parse_bool())),
             b'n' => {
@@ -215,63 +588,86 @@ impl<'input> JsonParser<'input> {
                 try!(self.expect('l'));
                 JsonValue::Null
             }
-            _ => unimplemented!(),
+            _ => return Err(self.unexpected_character(vec!['"', '{', '[', '-', 't', 'f', 'n'])),
         })
     }
 
-    fn parse_string(&self) -> ParseResult<Cow<'input, str>> {
+    pub(crate) fn parse_string(&self) -> ParseResult<Cow<'input, str>> {
+        let mut buf = Vec::new();
+
+        Ok(match try!(self.parse_string_into(&mut buf)) {
+            Some(string) => Cow::Borrowed(string),
+            // Runs of the input start and end next to ASCII bytes, so they
+            // are complete UTF-8 sequences, and escapes are replaced by ASCII
+            // bytes.
+            None => Cow::Owned(unsafe { String::from_utf8_unchecked(buf) }),
+        })
+    }
+
+    /// Parse a string. If it contains escapes, it is unescaped into `buf` and
+    /// `None` is returned, otherwise `buf` is left untouched and the string
+    /// is returned as a slice of the input.
+    pub(crate) fn parse_string_into<B>(&self, buf: &mut B) -> ParseResult<Option<&'input str>>
+        where B: for<'b> Extend<&'b u8>
+    {
         try!(self.expect('"'));
 
-        let mut byte_buf: Option<Vec<u8>> = None;
+        let bytes = self.input.as_bytes();
+        let mut escaped = false;
 
-        let string_start_idx = self.current_idx();
-        let mut string_end_idx;
-        let mut byte_slice;
+        let start = self.current_idx();
 
-        loop {
-            string_end_idx = self.current_idx();
-            byte_slice = &self.input.as_bytes()[string_start_idx..string_end_idx];
+        // Start of the bytes which haven't been copied to `buf` yet.
+        let mut run_start = start;
 
-            let b = self.current_byte().unwrap();
+        loop {
+            let rest = &bytes[self.current_idx()..];
+            self.next(swar::find_string_special(rest));
 
-            match b {
-                b'"' => break,
+            if exceeds(self.current_idx() - start, self.options.max_string_len) {
+                let max = self.options.max_string_len.unwrap();
+                return Err(self.limit_exceeded(start + max, ParseErrorKind::StringTooLong));
+            }
 
-                b'\\' => {
+            match self.current_byte() {
+                Some(b'"') => break,
+                Some(b'\\') => {
+                    escaped = true;
+                    buf.extend(&bytes[run_start..self.current_idx()]);
                     self.next(1);
-                    let b = self.current_byte().unwrap();
 
-                    let mut byte_buf_unwrapped = byte_buf.unwrap_or(byte_slice.to_vec());
+                    let unescaped: &[u8] = match self.current_byte() {
+                        Some(b'"') => b"\"",
+                        Some(b'\\') => b"\\",
+                        Some(b'n') => b"\n",
+                        Some(b'r') => b"\r",
+                        Some(b't') => b"\t",
+                        Some(_) => {
+                            return Err(self.unexpected_character(vec!['"', '\\', 'n', 'r', 't']))
+                        }
+                        None => return Err(self.unexpected_end_of_input()),
+                    };
 
-                    match b {
-                        b'"' => byte_buf_unwrapped.push(b'"'),
-                        b'\\' => byte_buf_unwrapped.push(b'\\'),
-                        b'n' => byte_buf_unwrapped.push(b'\n'),
-                        b'r' => byte_buf_unwrapped.push(b'\r'),
-                        b't' => byte_buf_unwrapped.push(b'\t'),
-                        c => panic!("{}", ::std::char::from_u32(c as u32).unwrap()),
-                    }
-                    byte_buf = Some(byte_buf_unwrapped);
-                }
-                c => {
-                    match byte_buf {
-                        Some(ref mut buf) => buf.push(c),
-                        None => {}
-                    }
+                    buf.extend(unescaped);
+                    self.next(1);
+                    run_start = self.current_idx();
                 }
+                // Control characters are let through as they are.
+                Some(_) => self.next(1),
+                None => return Err(self.unexpected_end_of_input()),
             }
-
-            self.next(1);
         }
 
-        let string_slice = unsafe { str::from_utf8_unchecked(byte_slice) };
+        let run = &self.input[run_start..self.current_idx()];
 
         try!(self.expect('"'));
 
-        Ok(match byte_buf {
-            Some(buf) => Cow::Owned(unsafe { String::from_utf8_unchecked(buf) }),
-            None => Cow::Borrowed(string_slice),
-        })
+        if escaped {
+            buf.extend(run.as_bytes());
+            Ok(None)
+        } else {
+            Ok(Some(run))
+        }
     }
 
     fn parse_number(&self) -> ParseResult<f64> {
@@ -287,28 +683,176 @@ impl<'input> JsonParser<'input> {
 
         let integer_part_start: usize = self.current_idx();
 
-        if self.current_byte().unwrap() == b'-' {
+        if self.current_byte() == Some(b'-') {
             self.next(1);
         }
 
-        while is_digit(self.current_byte().unwrap()) {
+        try!(self.expect_digit());
+
+        while self.current_byte().is_some_and(is_digit) {
             self.next(1);
         }
 
         let mut decimal_part_end: usize = self.current_idx();
 
-        if self.current_byte().unwrap() == b'.' {
+        if self.current_byte() == Some(b'.') {
             self.next(1);
             decimal_part_end += 1;
-            while is_digit(self.current_byte().unwrap()) {
+
+            try!(self.expect_digit());
+
+            while self.current_byte().is_some_and(is_digit) {
                 self.next(1);
                 decimal_part_end += 1;
             }
         }
 
+        if exceeds(decimal_part_end - integer_part_start, self.options.max_number_len) {
+            let max = self.options.max_number_len.unwrap();
+            return Err(self.limit_exceeded(integer_part_start + max, ParseErrorKind::NumberTooLong));
+        }
+
         let string = &self.input[integer_part_start..decimal_part_end];
 
-        Ok(string.parse().unwrap()) // it is unclear whether this can fail
+        Ok(string.parse().unwrap()) // there is at least one digit
+    }
+
+    fn expect_digit(&self) -> ParseResult<()> {
+        match self.current_byte() {
+            Some(b) if is_digit(b) => Ok(()),
+            _ => Err(self.unexpected_character(vec!['0', '1', '2', '3', '4', '5', '6', '7', '8', '9'])),
+        }
+    }
+
+    fn parse_raw_value(&self) -> ParseResult<RawValue<'input>> {
+        let start = self.current_idx();
+        let depth = self.depth.get();
+
+        if let Err(error) = self.skip_value() {
+            // Containers aren't left on errors, see `parse_recovering`.
+            self.depth.set(depth);
+            return Err(error);
+        }
+
+        Ok(RawValue::new(&self.input[start..self.current_idx()]))
+    }
+
+    pub(crate) fn unexpected_end_of_input(&self) -> ParseError {
+        ParseError::new(self.input,
+                        self.current_idx(),
+                        ParseErrorKind::UnexpectedEndOfInput)
+    }
+
+    pub(crate) fn unexpected_character(&self, expected: Vec<char>) -> ParseError {
+        match self.current_char() {
+            Some(c) => {
+                ParseError::new(self.input,
+                                self.current_idx(),
+                                ParseErrorKind::UnexpectedCharacter(c, expected))
+            }
+            None => self.unexpected_end_of_input(),
+        }
+    }
+
+    /// Check that the input contains a valid value and advance past it,
+    /// without building it. Like `parse_value`, this doesn't recurse.
+    pub(crate) fn skip_value(&self) -> ParseResult<()> {
+        // The closing brackets of the containers being skipped, innermost last.
+        let mut closes = Vec::new();
+
+        loop {
+            match self.current_byte() {
+                Some(b'"') => try!(self.skip_string()),
+                Some(b @ b'{') | Some(b @ b'[') => {
+                    try!(self.enter());
+                    try!(self.expect(b as char));
+                    self.expect_optional_whitespace();
+                    closes.push(if b == b'{' { b'}' } else { b']' });
+
+                    if !try!(self.skip_item_or_close(&mut closes)) {
+                        continue;
+                    }
+                }
+                _ => {
+                    try!(self.parse_scalar());
+                }
+            }
+
+            loop {
+                let close = match closes.last() {
+                    Some(&close) => close,
+                    None => return Ok(()),
+                };
+
+                self.expect_optional_whitespace();
+
+                match self.current_byte() {
+                    Some(b',') => {
+                        try!(self.expect(','));
+                        self.expect_optional_whitespace();
+                    }
+                    Some(b) if b == close => {}
+                    Some(_) => return Err(self.unexpected_character(vec![',', close as char])),
+                    None => return Err(self.unexpected_end_of_input()),
+                }
+
+                if !try!(self.skip_item_or_close(&mut closes)) {
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Same as `next_item`, minus the allocations. Returns whether the
+    /// innermost container was closed.
+    fn skip_item_or_close(&self, closes: &mut Vec<u8>) -> ParseResult<bool> {
+        let close = *closes.last().unwrap();
+
+        if self.current_byte() == Some(close) {
+            try!(self.expect(close as char));
+            self.leave();
+            closes.pop();
+            return Ok(true);
+        }
+
+        if close == b'}' {
+            try!(self.skip_string());
+            self.expect_optional_whitespace();
+            try!(self.expect(':'));
+            self.expect_optional_whitespace();
+        }
+
+        Ok(false)
+    }
+
+    fn skip_string(&self) -> ParseResult<()> {
+        try!(self.expect('"'));
+
+        loop {
+            let rest = &self.input.as_bytes()[self.current_idx()..];
+            self.next(swar::find_string_special(rest));
+
+            match self.current_byte() {
+                Some(b'"') => break,
+                Some(b'\\') => {
+                    self.next(1);
+
+                    match self.current_byte() {
+                        Some(b'"') | Some(b'\\') | Some(b'n') | Some(b'r') | Some(b't') => {}
+                        Some(_) => {
+                            return Err(self.unexpected_character(vec!['"', '\\', 'n', 'r', 't']))
+                        }
+                        None => return Err(self.unexpected_end_of_input()),
+                    }
+                }
+                Some(_) => {}
+                None => return Err(self.unexpected_end_of_input()),
+            }
+
+            self.next(1);
+        }
+
+        self.expect('"')
     }
 
     fn parse_bool(&self) -> ParseResult<bool> {
@@ -332,3 +876,67 @@ impl<'input> JsonParser<'input> {
         }
     }
 }
+
+/// The JSON Pointer of the innermost container of `stack`, or of the item
+/// being parsed in it if `item` is set. Each frame holds the key or index of
+/// the item being parsed in it, so this is only computed on errors.
+fn pointer(stack: &[Frame], item: bool) -> String {
+    let len = if item { stack.len() } else { stack.len().saturating_sub(1) };
+    let mut pointer = String::new();
+
+    for frame in &stack[..len] {
+        pointer.push('/');
+
+        match *frame {
+            Frame::Object(_, ref key) => pointer.push_str(&escape_token(key)),
+            Frame::Array(ref elements) => pointer.push_str(&elements.len().to_string()),
+        }
+    }
+
+    pointer
+}
+
+/// The result of `JsonParser::start_value`.
+enum Start<'input> {
+    Value(JsonValue<'input>),
+    Container(Frame<'input>),
+}
+
+/// An object or array being parsed by `JsonParser::parse_value`.
+enum Frame<'input> {
+    /// The members so far, and the key of the member being parsed.
+    Object(HashMap<Cow<'input, str>, JsonValue<'input>>, Cow<'input, str>),
+    Array(Vec<JsonValue<'input>>),
+}
+
+impl<'input> Frame<'input> {
+    fn close(&self) -> u8 {
+        match *self {
+            Frame::Object(..) => b'}',
+            Frame::Array(_) => b']',
+        }
+    }
+
+    fn len(&self) -> usize {
+        match *self {
+            Frame::Object(ref members, _) => members.len(),
+            Frame::Array(ref elements) => elements.len(),
+        }
+    }
+
+    fn push(&mut self, value: JsonValue<'input>) {
+        match *self {
+            Frame::Object(ref mut members, ref mut key) => {
+                members.insert(mem::replace(key, Cow::Borrowed("")), value);
+            }
+            Frame::Array(ref mut elements) => elements.push(value),
+        }
+    }
+
+    fn into_value(self) -> JsonValue<'input> {
+        match self {
+            Frame::Object(members, _) => JsonValue::Object(members),
+            Frame::Array(elements) => JsonValue::Array(elements),
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use std::borrow::Cow;
+use JsonValue;
+use pointer::{PointerError, parse_pointer};
+
+pub type PatchResult<T> = Result<T, PatchError>;
+
+/// A single JSON Patch (RFC 6902) operation.
+#[derive(PartialEq, Clone, Debug)]
+pub enum PatchOperation<'a> {
+    Add { path: String, value: JsonValue<'a> },
+    Remove { path: String },
+    Replace { path: String, value: JsonValue<'a> },
+    Move { from: String, path: String },
+    Copy { from: String, path: String },
+    Test { path: String, value: JsonValue<'a> },
+}
+
+#[derive(PartialEq, Debug)]
+pub enum PatchErrorKind {
+    /// The patch document doesn't describe a valid operation.
+    InvalidOperation(&'static str),
+    /// A `path` or `from` pointer couldn't be resolved.
+    Pointer(PointerError),
+    /// A `test` operation found a different value.
+    TestFailed,
+    /// A `move` operation tried to move a value into one of its children.
+    MoveIntoChild,
+}
+
+#[derive(PartialEq, Debug)]
+pub struct PatchError {
+    /// Index of the failing operation in the patch.
+    pub index: usize,
+    pub kind: PatchErrorKind,
+}
+
+impl PatchError {
+    pub fn new(index: usize, kind: PatchErrorKind) -> PatchError {
+        PatchError {
+            index: index,
+            kind: kind,
+        }
+    }
+}
+
+/// Convert a patch document (an array of operation objects) into a list of
+/// `PatchOperation`s.
+pub fn parse_patch<'a>(patch: &JsonValue<'a>) -> PatchResult<Vec<PatchOperation<'a>>> {
+    let operations = match *patch {
+        JsonValue::Array(ref operations) => operations,
+        _ => {
+            return Err(PatchError::new(0, PatchErrorKind::InvalidOperation("patch is not an array")))
+        }
+    };
+
+    let mut output = Vec::with_capacity(operations.len());
+
+    for (index, operation) in operations.iter().enumerate() {
+        output.push(try!(parse_operation(operation)
+            .map_err(|kind| PatchError::new(index, kind))));
+    }
+
+    Ok(output)
+}
+
+fn parse_operation<'a>(operation: &JsonValue<'a>) -> Result<PatchOperation<'a>, PatchErrorKind> {
+    let members = match *operation {
+        JsonValue::Object(ref members) => members,
+        _ => return Err(PatchErrorKind::InvalidOperation("operation is not an object")),
+    };
+
+    let pointer_member = |name: &'static str, missing: &'static str| {
+        match members.get(name) {
+            Some(&JsonValue::String(ref pointer)) => {
+                try!(parse_pointer(pointer).map_err(PatchErrorKind::Pointer));
+                Ok(pointer.to_string())
+            }
+            Some(_) => Err(PatchErrorKind::InvalidOperation("pointer is not a string")),
+            None => Err(PatchErrorKind::InvalidOperation(missing)),
+        }
+    };
+    let value_member = || {
+        members.get("value")
+               .cloned()
+               .ok_or(PatchErrorKind::InvalidOperation("missing `value` member"))
+    };
+
+    let op = match members.get("op") {
+        Some(&JsonValue::String(ref op)) => op,
+        Some(_) => return Err(PatchErrorKind::InvalidOperation("`op` is not a string")),
+        None => return Err(PatchErrorKind::InvalidOperation("missing `op` member")),
+    };
+    let path = try!(pointer_member("path", "missing `path` member"));
+
+    Ok(match &**op {
+        "add" => PatchOperation::Add { path: path, value: try!(value_member()) },
+        "remove" => PatchOperation::Remove { path: path },
+        "replace" => PatchOperation::Replace { path: path, value: try!(value_member()) },
+        "move" => {
+            PatchOperation::Move {
+                from: try!(pointer_member("from", "missing `from` member")),
+                path: path,
+            }
+        }
+        "copy" => {
+            PatchOperation::Copy {
+                from: try!(pointer_member("from", "missing `from` member")),
+                path: path,
+            }
+        }
+        "test" => PatchOperation::Test { path: path, value: try!(value_member()) },
+        _ => return Err(PatchErrorKind::InvalidOperation("unknown `op`")),
+    })
+}
+
+/// The inverse of a primitive change, used to roll back a partially applied
+/// patch.
+enum Undo<'a> {
+    Insert(String, JsonValue<'a>),
+    Remove(String),
+    Replace(String, JsonValue<'a>),
+}
+
+struct Transaction<'d, 'a: 'd> {
+    doc: &'d mut JsonValue<'a>,
+    undo_log: Vec<Undo<'a>>,
+}
+
+impl<'d, 'a> Transaction<'d, 'a> {
+    fn add(&mut self, path: &str, value: JsonValue<'a>) -> Result<(), PointerError> {
+        match try!(self.doc.pointer_insert(path, value)) {
+            Some(old) => self.undo_log.push(Undo::Replace(path.to_string(), old)),
+            None => {
+                // `-` has to be resolved to the actual index to undo an append
+                // to an array. In objects, it's an ordinary member name.
+                let path = match path.strip_suffix("/-") {
+                    Some(parent) => {
+                        match *try!(self.doc.pointer(parent)) {
+                            JsonValue::Array(ref vec) => format!("{}/{}", parent, vec.len() - 1),
+                            _ => path.to_string(),
+                        }
+                    }
+                    None => path.to_string(),
+                };
+
+                self.undo_log.push(Undo::Remove(path));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn remove(&mut self, path: &str) -> Result<(), PointerError> {
+        let old = try!(self.doc.pointer_remove(path));
+        self.undo_log.push(Undo::Insert(path.to_string(), old));
+        Ok(())
+    }
+
+    fn replace(&mut self, path: &str, value: JsonValue<'a>) -> Result<(), PointerError> {
+        let old = try!(self.doc.pointer_replace(path, value));
+        self.undo_log.push(Undo::Replace(path.to_string(), old));
+        Ok(())
+    }
+
+    fn apply(&mut self, operation: &PatchOperation<'a>) -> Result<(), PatchErrorKind> {
+        match *operation {
+            PatchOperation::Add { ref path, ref value } => {
+                self.add(path, value.clone()).map_err(PatchErrorKind::Pointer)
+            }
+            PatchOperation::Remove { ref path } => {
+                self.remove(path).map_err(PatchErrorKind::Pointer)
+            }
+            PatchOperation::Replace { ref path, ref value } => {
+                self.replace(path, value.clone()).map_err(PatchErrorKind::Pointer)
+            }
+            PatchOperation::Move { ref from, ref path } => {
+                if from == path {
+                    return self.doc.pointer(from).map(|_| ()).map_err(PatchErrorKind::Pointer);
+                }
+
+                if path.starts_with(&**from) && path[from.len()..].starts_with('/') {
+                    return Err(PatchErrorKind::MoveIntoChild);
+                }
+
+                let value = try!(self.doc.pointer(from).map_err(PatchErrorKind::Pointer)).clone();
+                try!(self.remove(from).map_err(PatchErrorKind::Pointer));
+                self.add(path, value).map_err(PatchErrorKind::Pointer)
+            }
+            PatchOperation::Copy { ref from, ref path } => {
+                let value = try!(self.doc.pointer(from).map_err(PatchErrorKind::Pointer)).clone();
+                self.add(path, value).map_err(PatchErrorKind::Pointer)
+            }
+            PatchOperation::Test { ref path, ref value } => {
+                if try!(self.doc.pointer(path).map_err(PatchErrorKind::Pointer)) == value {
+                    Ok(())
+                } else {
+                    Err(PatchErrorKind::TestFailed)
+                }
+            }
+        }
+    }
+
+    fn rollback(self) {
+        for undo in self.undo_log.into_iter().rev() {
+            let result = match undo {
+                Undo::Insert(path, value) => self.doc.pointer_insert(&path, value).map(|_| ()),
+                Undo::Remove(path) => self.doc.pointer_remove(&path).map(|_| ()),
+                Undo::Replace(path, value) => self.doc.pointer_replace(&path, value).map(|_| ()),
+            };
+
+            debug_assert!(result.is_ok(), "failed to roll back a patch operation");
+        }
+    }
+}
+
+/// Apply `operations` to `doc`, in order.
+///
+/// The patch is atomic: if any operation fails, the changes made by the
+/// previous ones are rolled back and `doc` is left untouched.
+pub fn apply_patch<'a>(doc: &mut JsonValue<'a>,
+                       operations: &[PatchOperation<'a>])
+                       -> PatchResult<()> {
+    let mut transaction = Transaction {
+        doc: doc,
+        undo_log: Vec::new(),
+    };
+
+    for (index, operation) in operations.iter().enumerate() {
+        if let Err(kind) = transaction.apply(operation) {
+            transaction.rollback();
+            return Err(PatchError::new(index, kind));
+        }
+    }
+
+    Ok(())
+}
+
+impl<'a> PatchOperation<'a> {
+    /// Convert this operation back to its JSON representation.
+    pub fn to_json(&self) -> JsonValue<'a> {
+        let mut members = ::std::collections::HashMap::new();
+
+        let (op, path) = match *self {
+            PatchOperation::Add { ref path, .. } => ("add", path),
+            PatchOperation::Remove { ref path } => ("remove", path),
+            PatchOperation::Replace { ref path, .. } => ("replace", path),
+            PatchOperation::Move { ref path, .. } => ("move", path),
+            PatchOperation::Copy { ref path, .. } => ("copy", path),
+            PatchOperation::Test { ref path, .. } => ("test", path),
+        };
+        members.insert(Cow::Borrowed("op"), JsonValue::String(Cow::Borrowed(op)));
+        members.insert(Cow::Borrowed("path"), JsonValue::String(Cow::Owned(path.clone())));
+
+        match *self {
+            PatchOperation::Add { ref value, .. } |
+            PatchOperation::Replace { ref value, .. } |
+            PatchOperation::Test { ref value, .. } => {
+                members.insert(Cow::Borrowed("value"), value.clone());
+            }
+            PatchOperation::Move { ref from, .. } |
+            PatchOperation::Copy { ref from, .. } => {
+                members.insert(Cow::Borrowed("from"), JsonValue::String(Cow::Owned(from.clone())));
+            }
+            PatchOperation::Remove { .. } => {}
+        }
+
+        JsonValue::Object(members)
+    }
+}
@@ -0,0 +1,228 @@
+use std::borrow::Cow;
+use std::mem;
+use JsonValue;
+
+pub type PointerResult<T> = Result<T, PointerError>;
+
+#[derive(Debug, PartialEq)]
+pub enum PointerError {
+    /// The pointer is not empty and doesn't start with `/`, or contains a `~`
+    /// that isn't followed by `0` or `1`.
+    InvalidSyntax,
+    /// The object has no member with this name.
+    MissingKey(String),
+    /// The reference token is not a valid array index (e.g. `01` or `foo`).
+    InvalidIndex(String),
+    /// The index is past the end of an array of the given length.
+    IndexOutOfRange(usize, usize),
+    /// The reference token tries to descend into a value which is neither an
+    /// object nor an array.
+    TypeMismatch(String),
+    /// The root of a document cannot be removed.
+    RootRemoval,
+}
+
+/// Split a JSON Pointer into its unescaped reference tokens.
+pub fn parse_pointer(pointer: &str) -> PointerResult<Vec<Cow<str>>> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    if !pointer.starts_with('/') {
+        return Err(PointerError::InvalidSyntax);
+    }
+
+    pointer[1..].split('/').map(unescape_token).collect()
+}
+
+/// Escape `~` and `/` so that `token` can be used as a reference token.
+pub fn escape_token(token: &str) -> Cow<str> {
+    if token.contains('~') || token.contains('/') {
+        Cow::Owned(token.replace('~', "~0").replace('/', "~1"))
+    } else {
+        Cow::Borrowed(token)
+    }
+}
+
+fn unescape_token(token: &str) -> PointerResult<Cow<str>> {
+    if !token.contains('~') {
+        return Ok(Cow::Borrowed(token));
+    }
+
+    let mut output = String::with_capacity(token.len());
+    let mut chars = token.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '~' {
+            match chars.next() {
+                Some('0') => output.push('~'),
+                Some('1') => output.push('/'),
+                _ => return Err(PointerError::InvalidSyntax),
+            }
+        } else {
+            output.push(c);
+        }
+    }
+
+    Ok(Cow::Owned(output))
+}
+
+/// Parse an array index. `-` designates the (nonexistent) element after the
+/// last one, so it resolves to `len`.
+fn parse_index(token: &str, len: usize) -> PointerResult<usize> {
+    if token == "-" {
+        return Ok(len);
+    }
+
+    let valid = !token.is_empty() && token.bytes().all(|b| b.is_ascii_digit()) &&
+                !(token.len() > 1 && token.starts_with('0'));
+
+    if !valid {
+        return Err(PointerError::InvalidIndex(token.to_string()));
+    }
+
+    token.parse().map_err(|_| PointerError::InvalidIndex(token.to_string()))
+}
+
+fn get_child<'v, 'a>(value: &'v JsonValue<'a>, token: &str) -> PointerResult<&'v JsonValue<'a>> {
+    match *value.resolved() {
+        JsonValue::Object(ref map) => {
+            map.get(token).ok_or_else(|| PointerError::MissingKey(token.to_string()))
+        }
+        JsonValue::Array(ref vec) => {
+            let idx = try!(parse_index(token, vec.len()));
+            vec.get(idx).ok_or(PointerError::IndexOutOfRange(idx, vec.len()))
+        }
+        _ => Err(PointerError::TypeMismatch(token.to_string())),
+    }
+}
+
+fn get_child_mut<'v, 'a>(value: &'v mut JsonValue<'a>,
+                         token: &str)
+                         -> PointerResult<&'v mut JsonValue<'a>> {
+    value.resolve_mut();
+
+    match *value {
+        JsonValue::Object(ref mut map) => {
+            map.get_mut(token).ok_or_else(|| PointerError::MissingKey(token.to_string()))
+        }
+        JsonValue::Array(ref mut vec) => {
+            let len = vec.len();
+            let idx = try!(parse_index(token, len));
+            vec.get_mut(idx).ok_or(PointerError::IndexOutOfRange(idx, len))
+        }
+        _ => Err(PointerError::TypeMismatch(token.to_string())),
+    }
+}
+
+fn resolve_mut<'v, 'a>(mut value: &'v mut JsonValue<'a>,
+                       tokens: &[Cow<str>])
+                       -> PointerResult<&'v mut JsonValue<'a>> {
+    for token in tokens {
+        value = try!(get_child_mut(value, token));
+    }
+
+    Ok(value)
+}
+
+impl<'a> JsonValue<'a> {
+    /// Look up the value designated by a JSON Pointer such as `/friends/2/name`.
+    ///
+    /// `Raw` values on the way are parsed, see `RawValue::value`.
+    pub fn pointer(&self, pointer: &str) -> PointerResult<&JsonValue<'a>> {
+        let tokens = try!(parse_pointer(pointer));
+        let mut value = self;
+
+        for token in &tokens {
+            value = try!(get_child(value, token));
+        }
+
+        Ok(value)
+    }
+
+    /// Mutable version of `pointer`. `Raw` values on the way are replaced
+    /// with their parsed value.
+    pub fn pointer_mut(&mut self, pointer: &str) -> PointerResult<&mut JsonValue<'a>> {
+        let tokens = try!(parse_pointer(pointer));
+        resolve_mut(self, &tokens)
+    }
+
+    /// Insert `new_value` at the location designated by `pointer`, following
+    /// the semantics of the JSON Patch `add` operation: object members are
+    /// created or overwritten, array elements are inserted before the given
+    /// index (or appended if the last token is `-`).
+    ///
+    /// Returns the overwritten value, if any.
+    pub fn pointer_insert(&mut self,
+                          pointer: &str,
+                          new_value: JsonValue<'a>)
+                          -> PointerResult<Option<JsonValue<'a>>> {
+        let tokens = try!(parse_pointer(pointer));
+
+        let (last, parents) = match tokens.split_last() {
+            Some(split) => split,
+            None => return Ok(Some(mem::replace(self, new_value))),
+        };
+
+        let parent = try!(resolve_mut(self, parents));
+        parent.resolve_mut();
+
+        match *parent {
+            JsonValue::Object(ref mut map) => {
+                Ok(map.insert(Cow::Owned(last.to_string()), new_value))
+            }
+            JsonValue::Array(ref mut vec) => {
+                let len = vec.len();
+                let idx = try!(parse_index(last, len));
+
+                if idx > len {
+                    return Err(PointerError::IndexOutOfRange(idx, len));
+                }
+
+                vec.insert(idx, new_value);
+                Ok(None)
+            }
+            _ => Err(PointerError::TypeMismatch(last.to_string())),
+        }
+    }
+
+    /// Remove the value designated by `pointer` and return it.
+    pub fn pointer_remove(&mut self, pointer: &str) -> PointerResult<JsonValue<'a>> {
+        let tokens = try!(parse_pointer(pointer));
+
+        let (last, parents) = match tokens.split_last() {
+            Some(split) => split,
+            None => return Err(PointerError::RootRemoval),
+        };
+
+        let parent = try!(resolve_mut(self, parents));
+        parent.resolve_mut();
+
+        match *parent {
+            JsonValue::Object(ref mut map) => {
+                map.remove(&**last).ok_or_else(|| PointerError::MissingKey(last.to_string()))
+            }
+            JsonValue::Array(ref mut vec) => {
+                let len = vec.len();
+                let idx = try!(parse_index(last, len));
+
+                if idx >= len {
+                    return Err(PointerError::IndexOutOfRange(idx, len));
+                }
+
+                Ok(vec.remove(idx))
+            }
+            _ => Err(PointerError::TypeMismatch(last.to_string())),
+        }
+    }
+
+    /// Replace the existing value designated by `pointer` with `new_value`
+    /// and return the old one.
+    pub fn pointer_replace(&mut self,
+                           pointer: &str,
+                           new_value: JsonValue<'a>)
+                           -> PointerResult<JsonValue<'a>> {
+        let target = try!(self.pointer_mut(pointer));
+        Ok(mem::replace(target, new_value))
+    }
+}
@@ -0,0 +1,111 @@
+use std::fmt;
+use std::mem;
+use std::sync::OnceLock;
+use JsonValue;
+use parser::{JsonParser, ParserOptions};
+use parse_error::ParseResult;
+
+/// A valid JSON object or array, kept as a slice of the input so that it is
+/// only parsed when needed.
+///
+/// Lookups go through raw values as if they had been parsed: `pointer`,
+/// JSONPath queries, `diff`, schema validation and `==` parse them on first
+/// access with `value`. Mutations such as `pointer_mut` replace them with
+/// their parsed value.
+pub struct RawValue<'a> {
+    json: &'a str,
+    /// The parsed value, once it has been needed. It borrows from `json`,
+    /// but is stored as `'static`, since a `JsonValue<'a>` inside a
+    /// `OnceLock` would make `JsonValue` invariant over `'a`.
+    value: OnceLock<Box<JsonValue<'static>>>,
+}
+
+impl<'a> RawValue<'a> {
+    /// The caller must make sure that `json` is valid.
+    pub(crate) fn new(json: &'a str) -> RawValue<'a> {
+        RawValue {
+            json: json,
+            value: OnceLock::new(),
+        }
+    }
+
+    /// The JSON text of the value, exactly as it appeared in the input.
+    pub fn get(&self) -> &'a str {
+        self.json
+    }
+
+    /// Parse the value. Strings are borrowed from the original input.
+    pub fn parse(&self) -> ParseResult<JsonValue<'a>> {
+        JsonParser::new(self.json).parse()
+    }
+
+    /// Parse the value with the given options, e.g. to only parse its first
+    /// level and keep nested containers raw.
+    pub fn parse_with_options(&self, options: ParserOptions) -> ParseResult<JsonValue<'a>> {
+        JsonParser::with_options(self.json, options).parse()
+    }
+
+    /// The parsed value, which is parsed on the first call and then kept.
+    pub fn value(&self) -> &JsonValue<'a> {
+        self.value.get_or_init(|| {
+            // The value only borrows from `json`, which outlives `'a`, and is
+            // only handed out as a `JsonValue<'a>`.
+            Box::new(unsafe { mem::transmute::<JsonValue<'a>, JsonValue<'static>>(self.parse_valid()) })
+        })
+    }
+
+    /// Move the parsed value out, parsing it if needed.
+    pub(crate) fn take_value(&mut self) -> JsonValue<'a> {
+        match self.value.take() {
+            Some(value) => *value,
+            None => self.parse_valid(),
+        }
+    }
+
+    /// Raw values were validated by the parser which created them, so they
+    /// can only fail to parse by exceeding a limit, and the only one applying
+    /// by default is the depth.
+    fn parse_valid(&self) -> JsonValue<'a> {
+        let options = ParserOptions { max_depth: usize::MAX, ..ParserOptions::default() };
+        self.parse_with_options(options).expect("raw values are valid JSON")
+    }
+}
+
+impl<'a> Clone for RawValue<'a> {
+    fn clone(&self) -> RawValue<'a> {
+        RawValue::new(self.json)
+    }
+}
+
+impl<'a> PartialEq for RawValue<'a> {
+    fn eq(&self, other: &RawValue<'a>) -> bool {
+        self.json == other.json || self.value() == other.value()
+    }
+}
+
+impl<'a> fmt::Debug for RawValue<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RawValue").field("json", &self.json).finish()
+    }
+}
+
+impl<'a> JsonValue<'a> {
+    /// The parsed value of a `Raw` value, or else the value itself.
+    pub(crate) fn resolved(&self) -> &JsonValue<'a> {
+        match *self {
+            JsonValue::Raw(ref raw) => raw.value(),
+            _ => self,
+        }
+    }
+
+    /// Replace a `Raw` value with its parsed value, so that it can be
+    /// modified.
+    pub(crate) fn resolve_mut(&mut self) {
+        let value = match *self {
+            JsonValue::Raw(ref mut raw) => raw.take_value(),
+            _ => return,
+        };
+
+        *self = value;
+    }
+}
@@ -0,0 +1,838 @@
+//! JSON Schema validation (draft 2020-12, core and validation vocabularies).
+//!
+//! Supported keywords: `type`, `enum`, `const`, `properties`,
+//! `patternProperties`, `additionalProperties`, `propertyNames`, `required`,
+//! `dependentRequired`, `minProperties`, `maxProperties`, `prefixItems`,
+//! `items`, `contains`, `minContains`, `maxContains`, `minItems`,
+//! `maxItems`, `uniqueItems`, `minimum`, `maximum`, `exclusiveMinimum`,
+//! `exclusiveMaximum`, `multipleOf`, `minLength`, `maxLength`, `pattern`,
+//! `allOf`, `anyOf`, `oneOf`, `not`, `if`/`then`/`else`, `$defs` and local
+//! `$ref`s (`#` followed by a JSON Pointer). Unknown keywords are ignored.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use regex_lite::Regex;
+use JsonValue;
+use pointer::escape_token;
+
+pub type SchemaResult<T> = Result<T, SchemaError>;
+
+/// An error found while compiling a schema.
+#[derive(PartialEq, Debug)]
+pub struct SchemaError {
+    /// JSON Pointer to the offending keyword in the schema.
+    pub schema_path: String,
+    pub message: &'static str,
+}
+
+/// A failed assertion.
+#[derive(PartialEq, Debug)]
+pub struct ValidationError {
+    /// JSON Pointer to the invalid value in the instance.
+    pub instance_path: String,
+    /// JSON Pointer to the failing keyword in the schema.
+    pub schema_path: String,
+    pub message: String,
+}
+
+/// A compiled schema.
+#[derive(Debug)]
+pub struct Schema {
+    /// All the subschemas, the root being the first one. Subschemas refer
+    /// to each other by index, so that recursive `$ref`s can be compiled.
+    nodes: Vec<SchemaNode>,
+}
+
+type NodeId = usize;
+
+#[derive(Debug)]
+enum SchemaNode {
+    /// `true` or `false`, and the path of the schema.
+    Bool(bool, String),
+    Keywords(Box<Keywords>),
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+enum Type {
+    Null,
+    Boolean,
+    Object,
+    Array,
+    Number,
+    Integer,
+    String,
+}
+
+/// The names of the types, as used by the `type` keyword.
+const TYPES: [(&str, Type); 7] = [("null", Type::Null),
+                                  ("boolean", Type::Boolean),
+                                  ("object", Type::Object),
+                                  ("array", Type::Array),
+                                  ("number", Type::Number),
+                                  ("integer", Type::Integer),
+                                  ("string", Type::String)];
+
+#[derive(Default, Debug)]
+struct Keywords {
+    path: String,
+
+    reference: Option<NodeId>,
+    all_of: Vec<NodeId>,
+    any_of: Vec<NodeId>,
+    one_of: Vec<NodeId>,
+    not: Option<NodeId>,
+    if_then_else: Option<(NodeId, Option<NodeId>, Option<NodeId>)>,
+
+    types: Option<Vec<Type>>,
+    enumeration: Option<Vec<JsonValue<'static>>>,
+    constant: Option<JsonValue<'static>>,
+
+    properties: Vec<(String, NodeId)>,
+    pattern_properties: Vec<(Regex, NodeId)>,
+    additional_properties: Option<NodeId>,
+    property_names: Option<NodeId>,
+    required: Vec<String>,
+    dependent_required: Vec<(String, Vec<String>)>,
+    min_properties: Option<usize>,
+    max_properties: Option<usize>,
+
+    prefix_items: Vec<NodeId>,
+    items: Option<NodeId>,
+    contains: Option<NodeId>,
+    min_contains: Option<usize>,
+    max_contains: Option<usize>,
+    min_items: Option<usize>,
+    max_items: Option<usize>,
+    unique_items: bool,
+
+    minimum: Option<f64>,
+    maximum: Option<f64>,
+    exclusive_minimum: Option<f64>,
+    exclusive_maximum: Option<f64>,
+    multiple_of: Option<f64>,
+
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+    pattern: Option<Regex>,
+}
+
+/// Copy `value` so that it no longer borrows from its input.
+fn to_owned_value(value: &JsonValue) -> JsonValue<'static> {
+    match *value {
+        JsonValue::String(ref s) => JsonValue::String(Cow::Owned(s.to_string())),
+        JsonValue::Number(n) => JsonValue::Number(n),
+        JsonValue::Boolean(b) => JsonValue::Boolean(b),
+        JsonValue::Null => JsonValue::Null,
+        JsonValue::Invalid => JsonValue::Invalid,
+        JsonValue::Raw(ref raw) => to_owned_value(raw.value()),
+        JsonValue::Array(ref vec) => JsonValue::Array(vec.iter().map(to_owned_value).collect()),
+        JsonValue::Object(ref map) => {
+            JsonValue::Object(map.iter()
+                                 .map(|(k, v)| (Cow::Owned(k.to_string()), to_owned_value(v)))
+                                 .collect())
+        }
+    }
+}
+
+fn child_path(path: &str, token: &str) -> String {
+    format!("{}/{}", path, escape_token(token))
+}
+
+struct Compiler<'s, 'a: 's> {
+    root: &'s JsonValue<'a>,
+    nodes: Vec<SchemaNode>,
+    /// Maps the JSON Pointer of each compiled subschema to its id.
+    compiled: HashMap<String, NodeId>,
+}
+
+impl<'s, 'a> Compiler<'s, 'a> {
+    fn error<T>(path: &str, keyword: &str, message: &'static str) -> SchemaResult<T> {
+        Err(SchemaError {
+            schema_path: child_path(path, keyword),
+            message: message,
+        })
+    }
+
+    /// Compile the subschema located at `path`, unless it already has been.
+    fn compile(&mut self, path: String) -> SchemaResult<NodeId> {
+        if let Some(&id) = self.compiled.get(&path) {
+            return Ok(id);
+        }
+
+        // References are checked before being compiled, and other
+        // subschemas are found by iterating over their parent.
+        let schema = self.root.pointer(&path).unwrap();
+
+        // Register the node before compiling its children, so that cycles
+        // of references terminate.
+        let id = self.nodes.len();
+        self.nodes.push(SchemaNode::Bool(true, String::new()));
+        self.compiled.insert(path.clone(), id);
+
+        let node = match *schema {
+            JsonValue::Boolean(b) => SchemaNode::Bool(b, path),
+            JsonValue::Object(ref members) => {
+                SchemaNode::Keywords(Box::new(try!(self.compile_keywords(path, members))))
+            }
+            _ => {
+                return Err(SchemaError {
+                    schema_path: path,
+                    message: "schema must be an object or a boolean",
+                })
+            }
+        };
+
+        self.nodes[id] = node;
+        Ok(id)
+    }
+
+    fn compile_keywords(&mut self,
+                        path: String,
+                        members: &'s HashMap<Cow<'a, str>, JsonValue<'a>>)
+                        -> SchemaResult<Keywords> {
+        let mut keywords = Keywords::default();
+
+        for (keyword, value) in members {
+            let keyword = &**keyword;
+
+            match keyword {
+                "$ref" => {
+                    let reference = match *value {
+                        JsonValue::String(ref s) => s,
+                        _ => return Compiler::error(&path, keyword, "expected a string"),
+                    };
+
+                    if !reference.starts_with('#') {
+                        return Compiler::error(&path, keyword, "only local references are supported");
+                    }
+
+                    let pointer = match percent_decode(&reference[1..]) {
+                        Some(pointer) => pointer,
+                        None => return Compiler::error(&path, keyword, "invalid percent-encoding"),
+                    };
+
+                    if self.root.pointer(&pointer).is_err() {
+                        return Compiler::error(&path, keyword, "unresolvable reference");
+                    }
+
+                    keywords.reference = Some(try!(self.compile(pointer)));
+                }
+                "allOf" | "anyOf" | "oneOf" => {
+                    let ids = try!(self.compile_array(&path, keyword, value));
+
+                    match keyword {
+                        "allOf" => keywords.all_of = ids,
+                        "anyOf" => keywords.any_of = ids,
+                        _ => keywords.one_of = ids,
+                    }
+                }
+                "not" => keywords.not = Some(try!(self.compile(child_path(&path, keyword)))),
+                "if" => {
+                    let then = if members.contains_key("then") {
+                        Some(try!(self.compile(child_path(&path, "then"))))
+                    } else {
+                        None
+                    };
+                    let otherwise = if members.contains_key("else") {
+                        Some(try!(self.compile(child_path(&path, "else"))))
+                    } else {
+                        None
+                    };
+
+                    keywords.if_then_else = Some((try!(self.compile(child_path(&path, keyword))),
+                                                  then,
+                                                  otherwise));
+                }
+                "type" => {
+                    let names = match *value {
+                        JsonValue::String(ref name) => vec![&**name],
+                        JsonValue::Array(ref names) => {
+                            let mut output = Vec::new();
+
+                            for name in names {
+                                match *name {
+                                    JsonValue::String(ref name) => output.push(&**name),
+                                    _ => return Compiler::error(&path, keyword, "expected a string"),
+                                }
+                            }
+
+                            output
+                        }
+                        _ => return Compiler::error(&path, keyword, "expected a string or an array"),
+                    };
+
+                    let mut types = Vec::new();
+
+                    for name in names {
+                        match TYPES.iter().find(|&&(type_name, _)| type_name == name) {
+                            Some(&(_, ty)) => types.push(ty),
+                            None => return Compiler::error(&path, keyword, "unknown type"),
+                        }
+                    }
+
+                    keywords.types = Some(types);
+                }
+                "enum" => {
+                    match *value {
+                        JsonValue::Array(ref values) => {
+                            keywords.enumeration = Some(values.iter().map(to_owned_value).collect())
+                        }
+                        _ => return Compiler::error(&path, keyword, "expected an array"),
+                    }
+                }
+                "const" => keywords.constant = Some(to_owned_value(value)),
+                "properties" | "patternProperties" => {
+                    let properties = match *value {
+                        JsonValue::Object(ref properties) => properties,
+                        _ => return Compiler::error(&path, keyword, "expected an object"),
+                    };
+                    let keyword_path = child_path(&path, keyword);
+
+                    for name in properties.keys() {
+                        let id = try!(self.compile(child_path(&keyword_path, name)));
+
+                        if keyword == "properties" {
+                            keywords.properties.push((name.to_string(), id));
+                        } else {
+                            match Regex::new(name) {
+                                Ok(regex) => keywords.pattern_properties.push((regex, id)),
+                                Err(_) => return Compiler::error(&keyword_path, name, "invalid regex"),
+                            }
+                        }
+                    }
+                }
+                "additionalProperties" => {
+                    keywords.additional_properties = Some(try!(self.compile(child_path(&path, keyword))))
+                }
+                "propertyNames" => {
+                    keywords.property_names = Some(try!(self.compile(child_path(&path, keyword))))
+                }
+                "required" => keywords.required = try!(string_array(&path, keyword, value)),
+                "dependentRequired" => {
+                    let dependencies = match *value {
+                        JsonValue::Object(ref dependencies) => dependencies,
+                        _ => return Compiler::error(&path, keyword, "expected an object"),
+                    };
+                    let keyword_path = child_path(&path, keyword);
+
+                    for (name, required) in dependencies {
+                        let required = try!(string_array(&keyword_path, name, required));
+                        keywords.dependent_required.push((name.to_string(), required));
+                    }
+                }
+                "prefixItems" => keywords.prefix_items = try!(self.compile_array(&path, keyword, value)),
+                "items" => keywords.items = Some(try!(self.compile(child_path(&path, keyword)))),
+                "contains" => keywords.contains = Some(try!(self.compile(child_path(&path, keyword)))),
+                "uniqueItems" => {
+                    match *value {
+                        JsonValue::Boolean(b) => keywords.unique_items = b,
+                        _ => return Compiler::error(&path, keyword, "expected a boolean"),
+                    }
+                }
+                "minProperties" => keywords.min_properties = Some(try!(count(&path, keyword, value))),
+                "maxProperties" => keywords.max_properties = Some(try!(count(&path, keyword, value))),
+                "minContains" => keywords.min_contains = Some(try!(count(&path, keyword, value))),
+                "maxContains" => keywords.max_contains = Some(try!(count(&path, keyword, value))),
+                "minItems" => keywords.min_items = Some(try!(count(&path, keyword, value))),
+                "maxItems" => keywords.max_items = Some(try!(count(&path, keyword, value))),
+                "minLength" => keywords.min_length = Some(try!(count(&path, keyword, value))),
+                "maxLength" => keywords.max_length = Some(try!(count(&path, keyword, value))),
+                "minimum" => keywords.minimum = Some(try!(number(&path, keyword, value))),
+                "maximum" => keywords.maximum = Some(try!(number(&path, keyword, value))),
+                "exclusiveMinimum" => keywords.exclusive_minimum = Some(try!(number(&path, keyword, value))),
+                "exclusiveMaximum" => keywords.exclusive_maximum = Some(try!(number(&path, keyword, value))),
+                "multipleOf" => {
+                    let n = try!(number(&path, keyword, value));
+
+                    if n <= 0.0 {
+                        return Compiler::error(&path, keyword, "expected a strictly positive number");
+                    }
+
+                    keywords.multiple_of = Some(n);
+                }
+                "pattern" => {
+                    match *value {
+                        JsonValue::String(ref pattern) => {
+                            match Regex::new(pattern) {
+                                Ok(regex) => keywords.pattern = Some(regex),
+                                Err(_) => return Compiler::error(&path, keyword, "invalid regex"),
+                            }
+                        }
+                        _ => return Compiler::error(&path, keyword, "expected a string"),
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // Sort by name so that errors are reported in a deterministic order.
+        keywords.properties.sort_by(|a, b| a.0.cmp(&b.0));
+        keywords.path = path;
+
+        Ok(keywords)
+    }
+
+    fn compile_array(&mut self,
+                     path: &str,
+                     keyword: &str,
+                     value: &JsonValue)
+                     -> SchemaResult<Vec<NodeId>> {
+        let len = match *value {
+            JsonValue::Array(ref schemas) if !schemas.is_empty() => schemas.len(),
+            _ => return Compiler::error(path, keyword, "expected a non-empty array"),
+        };
+        let keyword_path = child_path(path, keyword);
+
+        (0..len).map(|idx| self.compile(format!("{}/{}", keyword_path, idx))).collect()
+    }
+}
+
+fn count(path: &str, keyword: &str, value: &JsonValue) -> SchemaResult<usize> {
+    match *value {
+        JsonValue::Number(n) if n >= 0.0 && n.fract() == 0.0 => Ok(n as usize),
+        _ => Compiler::error(path, keyword, "expected a non-negative integer"),
+    }
+}
+
+fn number(path: &str, keyword: &str, value: &JsonValue) -> SchemaResult<f64> {
+    match *value {
+        JsonValue::Number(n) => Ok(n),
+        _ => Compiler::error(path, keyword, "expected a number"),
+    }
+}
+
+fn string_array(path: &str, keyword: &str, value: &JsonValue) -> SchemaResult<Vec<String>> {
+    let values = match *value {
+        JsonValue::Array(ref values) => values,
+        _ => return Compiler::error(path, keyword, "expected an array of strings"),
+    };
+
+    let mut output = Vec::with_capacity(values.len());
+
+    for value in values {
+        match *value {
+            JsonValue::String(ref s) => output.push(s.to_string()),
+            _ => return Compiler::error(path, keyword, "expected an array of strings"),
+        }
+    }
+
+    Ok(output)
+}
+
+/// Decode the `%XX` escapes of a URI fragment.
+fn percent_decode(fragment: &str) -> Option<String> {
+    let bytes = fragment.as_bytes();
+    let mut output = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            match fragment.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                Some(b) => output.push(b),
+                None => return None,
+            }
+            i += 3;
+        } else {
+            output.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    String::from_utf8(output).ok()
+}
+
+impl Keywords {
+    /// The subschemas applied to the instance itself rather than to one of
+    /// its children, with the keyword they are found under.
+    fn in_place(&self) -> Vec<(&'static str, NodeId)> {
+        let mut subschemas = Vec::new();
+
+        if let Some(id) = self.reference {
+            subschemas.push(("$ref", id));
+        }
+
+        subschemas.extend(self.all_of.iter().map(|&id| ("allOf", id)));
+        subschemas.extend(self.any_of.iter().map(|&id| ("anyOf", id)));
+        subschemas.extend(self.one_of.iter().map(|&id| ("oneOf", id)));
+
+        if let Some(id) = self.not {
+            subschemas.push(("not", id));
+        }
+
+        if let Some((cond, then, otherwise)) = self.if_then_else {
+            subschemas.push(("if", cond));
+            subschemas.extend(then.map(|id| ("then", id)));
+            subschemas.extend(otherwise.map(|id| ("else", id)));
+        }
+
+        subschemas
+    }
+}
+
+#[derive(PartialEq, Clone, Copy)]
+enum Visit {
+    New,
+    InProgress,
+    Done,
+}
+
+/// Reject cycles of subschemas applied to the same instance, such as
+/// `{"$ref": "#"}`, on which validation would never terminate.
+fn check_cycles(nodes: &[SchemaNode], id: NodeId, visits: &mut [Visit]) -> SchemaResult<()> {
+    if visits[id] != Visit::New {
+        return Ok(());
+    }
+
+    visits[id] = Visit::InProgress;
+
+    if let SchemaNode::Keywords(ref keywords) = nodes[id] {
+        for (keyword, child) in keywords.in_place() {
+            if visits[child] == Visit::InProgress {
+                return Compiler::error(&keywords.path, keyword, "reference cycle");
+            }
+
+            try!(check_cycles(nodes, child, visits));
+        }
+    }
+
+    visits[id] = Visit::Done;
+    Ok(())
+}
+
+fn type_name(ty: Type) -> &'static str {
+    TYPES.iter().find(|&&(_, t)| t == ty).unwrap().0
+}
+
+fn type_matches(value: &JsonValue, ty: Type) -> bool {
+    match (value, ty) {
+        (&JsonValue::Null, Type::Null) |
+        (&JsonValue::Boolean(_), Type::Boolean) |
+        (&JsonValue::Object(_), Type::Object) |
+        (&JsonValue::Array(_), Type::Array) |
+        (&JsonValue::Number(_), Type::Number) |
+        (&JsonValue::String(_), Type::String) => true,
+        (&JsonValue::Number(n), Type::Integer) => n.fract() == 0.0,
+        _ => false,
+    }
+}
+
+impl Schema {
+    /// Compile a schema. All the subschemas and references are resolved
+    /// eagerly, so the schema can't fail to compile later on.
+    pub fn compile(schema: &JsonValue) -> SchemaResult<Schema> {
+        // Parse the `Raw` values of lazily parsed schemas, so that keywords
+        // don't have to handle them.
+        let schema = to_owned_value(schema);
+
+        let mut compiler = Compiler {
+            root: &schema,
+            nodes: Vec::new(),
+            compiled: HashMap::new(),
+        };
+
+        try!(compiler.compile(String::new()));
+
+        let mut visits = vec![Visit::New; compiler.nodes.len()];
+        for id in 0..compiler.nodes.len() {
+            try!(check_cycles(&compiler.nodes, id, &mut visits));
+        }
+
+        Ok(Schema { nodes: compiler.nodes })
+    }
+
+    /// Check whether `instance` is valid against this schema.
+    pub fn is_valid(&self, instance: &JsonValue) -> bool {
+        let mut errors = Vec::new();
+        self.validate_node(0, instance, "", &mut errors);
+        errors.is_empty()
+    }
+
+    /// Validate `instance`, reporting every failed assertion.
+    pub fn validate(&self, instance: &JsonValue) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        self.validate_node(0, instance, "", &mut errors);
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn is_valid_node(&self, id: NodeId, instance: &JsonValue) -> bool {
+        let mut errors = Vec::new();
+        self.validate_node(id, instance, "", &mut errors);
+        errors.is_empty()
+    }
+
+    fn validate_node(&self,
+                     id: NodeId,
+                     instance: &JsonValue,
+                     instance_path: &str,
+                     errors: &mut Vec<ValidationError>) {
+        let instance = instance.resolved();
+
+        let keywords = match self.nodes[id] {
+            SchemaNode::Bool(true, _) => return,
+            SchemaNode::Bool(false, ref path) => {
+                errors.push(ValidationError {
+                    instance_path: instance_path.to_string(),
+                    schema_path: path.clone(),
+                    message: "no value is valid against the `false` schema".to_string(),
+                });
+                return;
+            }
+            SchemaNode::Keywords(ref keywords) => keywords,
+        };
+
+        let mut error = |keyword: &str, message: String| {
+            errors.push(ValidationError {
+                instance_path: instance_path.to_string(),
+                schema_path: child_path(&keywords.path, keyword),
+                message: message,
+            })
+        };
+
+        if let Some(ref types) = keywords.types {
+            if !types.iter().any(|&ty| type_matches(instance, ty)) {
+                let names: Vec<String> = types.iter().map(|&ty| format!("{:?}", type_name(ty))).collect();
+                error("type", format!("expected one of {}", names.join(", ")));
+            }
+        }
+
+        if let Some(ref values) = keywords.enumeration {
+            if !values.iter().any(|value| value == instance) {
+                error("enum", "value is not one of the enumerated values".to_string());
+            }
+        }
+
+        if let Some(ref value) = keywords.constant {
+            if value != instance {
+                error("const", "value is not equal to the constant".to_string());
+            }
+        }
+
+        let any_valid = |ids: &[NodeId]| ids.iter().filter(|&&id| self.is_valid_node(id, instance)).count();
+
+        if !keywords.any_of.is_empty() && any_valid(&keywords.any_of) == 0 {
+            error("anyOf", "value is not valid against any subschema".to_string());
+        }
+
+        if !keywords.one_of.is_empty() {
+            let valid = any_valid(&keywords.one_of);
+
+            if valid != 1 {
+                error("oneOf",
+                      format!("value is valid against {} subschemas instead of one", valid));
+            }
+        }
+
+        if let Some(not) = keywords.not {
+            if self.is_valid_node(not, instance) {
+                error("not", "value is valid against the negated subschema".to_string());
+            }
+        }
+
+        match *instance {
+            JsonValue::Number(n) => {
+                if let Some(minimum) = keywords.minimum {
+                    if n < minimum {
+                        error("minimum", format!("{} is less than {}", n, minimum));
+                    }
+                }
+
+                if let Some(maximum) = keywords.maximum {
+                    if n > maximum {
+                        error("maximum", format!("{} is greater than {}", n, maximum));
+                    }
+                }
+
+                if let Some(minimum) = keywords.exclusive_minimum {
+                    if n <= minimum {
+                        error("exclusiveMinimum", format!("{} is not greater than {}", n, minimum));
+                    }
+                }
+
+                if let Some(maximum) = keywords.exclusive_maximum {
+                    if n >= maximum {
+                        error("exclusiveMaximum", format!("{} is not less than {}", n, maximum));
+                    }
+                }
+
+                if let Some(divisor) = keywords.multiple_of {
+                    let quotient = n / divisor;
+
+                    if quotient.is_finite() && (quotient - quotient.round()).abs() > 1e-9 {
+                        error("multipleOf", format!("{} is not a multiple of {}", n, divisor));
+                    }
+                }
+            }
+            JsonValue::String(ref s) => {
+                let len = s.chars().count();
+
+                if let Some(min_length) = keywords.min_length {
+                    if len < min_length {
+                        error("minLength", format!("string is shorter than {} characters", min_length));
+                    }
+                }
+
+                if let Some(max_length) = keywords.max_length {
+                    if len > max_length {
+                        error("maxLength", format!("string is longer than {} characters", max_length));
+                    }
+                }
+
+                if let Some(ref pattern) = keywords.pattern {
+                    if !pattern.is_match(s) {
+                        error("pattern", format!("string doesn't match `{}`", pattern.as_str()));
+                    }
+                }
+            }
+            JsonValue::Array(ref elements) => {
+                if let Some(min_items) = keywords.min_items {
+                    if elements.len() < min_items {
+                        error("minItems", format!("array has fewer than {} items", min_items));
+                    }
+                }
+
+                if let Some(max_items) = keywords.max_items {
+                    if elements.len() > max_items {
+                        error("maxItems", format!("array has more than {} items", max_items));
+                    }
+                }
+
+                if keywords.unique_items {
+                    let duplicate = (0..elements.len())
+                        .any(|i| elements[i + 1..].iter().any(|other| *other == elements[i]));
+
+                    if duplicate {
+                        error("uniqueItems", "array items are not unique".to_string());
+                    }
+                }
+
+                if let Some(contains) = keywords.contains {
+                    let matches = elements.iter()
+                                          .filter(|element| self.is_valid_node(contains, element))
+                                          .count();
+                    let min_contains = keywords.min_contains.unwrap_or(1);
+
+                    if matches < min_contains {
+                        error("contains",
+                              format!("array contains fewer than {} matching items", min_contains));
+                    }
+
+                    if let Some(max_contains) = keywords.max_contains {
+                        if matches > max_contains {
+                            error("maxContains",
+                                  format!("array contains more than {} matching items", max_contains));
+                        }
+                    }
+                }
+            }
+            JsonValue::Object(ref members) => {
+                if let Some(min_properties) = keywords.min_properties {
+                    if members.len() < min_properties {
+                        error("minProperties", format!("object has fewer than {} members", min_properties));
+                    }
+                }
+
+                if let Some(max_properties) = keywords.max_properties {
+                    if members.len() > max_properties {
+                        error("maxProperties", format!("object has more than {} members", max_properties));
+                    }
+                }
+
+                for name in &keywords.required {
+                    if !members.contains_key(&**name) {
+                        error("required", format!("missing required member `{}`", name));
+                    }
+                }
+
+                for (name, required) in &keywords.dependent_required {
+                    if !members.contains_key(&**name) {
+                        continue;
+                    }
+
+                    for dependency in required {
+                        if !members.contains_key(&**dependency) {
+                            error("dependentRequired",
+                                  format!("member `{}` requires member `{}`", name, dependency));
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        // Applicators which report the errors of their subschemas.
+
+        if let Some(reference) = keywords.reference {
+            self.validate_node(reference, instance, instance_path, errors);
+        }
+
+        for &id in &keywords.all_of {
+            self.validate_node(id, instance, instance_path, errors);
+        }
+
+        if let Some((condition, then, otherwise)) = keywords.if_then_else {
+            let branch = if self.is_valid_node(condition, instance) { then } else { otherwise };
+
+            if let Some(branch) = branch {
+                self.validate_node(branch, instance, instance_path, errors);
+            }
+        }
+
+        match *instance {
+            JsonValue::Array(ref elements) => {
+                for (idx, element) in elements.iter().enumerate() {
+                    let id = match keywords.prefix_items.get(idx) {
+                        Some(&id) => id,
+                        None => {
+                            match keywords.items {
+                                Some(id) => id,
+                                None => break,
+                            }
+                        }
+                    };
+
+                    let path = format!("{}/{}", instance_path, idx);
+                    self.validate_node(id, element, &path, errors);
+                }
+            }
+            JsonValue::Object(ref members) => {
+                let mut names: Vec<_> = members.keys().collect();
+                names.sort();
+
+                for name in names {
+                    let value = &members[name];
+                    let path = child_path(instance_path, name);
+                    let mut evaluated = false;
+
+                    if let Some(&(_, id)) = keywords.properties.iter().find(|p| p.0 == **name) {
+                        evaluated = true;
+                        self.validate_node(id, value, &path, errors);
+                    }
+
+                    for &(ref regex, id) in &keywords.pattern_properties {
+                        if regex.is_match(name) {
+                            evaluated = true;
+                            self.validate_node(id, value, &path, errors);
+                        }
+                    }
+
+                    if !evaluated {
+                        if let Some(id) = keywords.additional_properties {
+                            self.validate_node(id, value, &path, errors);
+                        }
+                    }
+
+                    if let Some(id) = keywords.property_names {
+                        let name = JsonValue::String(Cow::Borrowed(&**name));
+                        self.validate_node(id, &name, &path, errors);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
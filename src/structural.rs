@@ -0,0 +1,564 @@
+//! Two-stage parsing, in the style of simdjson.
+//!
+//! Stage 1 scans the input 64 bytes at a time and records the position of
+//! every structural character (`{ } [ ] : ,`), of every unescaped quote and
+//! of the first byte of every literal or number. Stage 2 then builds the
+//! `JsonValue` by walking this index instead of looking at every byte.
+//!
+//! On x86_64, stage 1 uses AVX2 or SSE2 instructions depending on what the
+//! CPU supports. Other targets use a portable implementation.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use JsonValue;
+use parser::{JsonParser, DEFAULT_MAX_DEPTH};
+use parse_error::{ParseError, ParseErrorKind, ParseResult};
+
+/// A stage 1 implementation.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Implementation {
+    Scalar,
+    Sse2,
+    Avx2,
+}
+
+impl Implementation {
+    /// The fastest implementation supported by the current CPU.
+    pub fn detect() -> Implementation {
+        if Implementation::Avx2.is_supported() {
+            Implementation::Avx2
+        } else if Implementation::Sse2.is_supported() {
+            Implementation::Sse2
+        } else {
+            Implementation::Scalar
+        }
+    }
+
+    pub fn is_supported(self) -> bool {
+        match self {
+            Implementation::Scalar => true,
+            #[cfg(target_arch = "x86_64")]
+            Implementation::Sse2 => is_x86_feature_detected!("sse2"),
+            #[cfg(target_arch = "x86_64")]
+            Implementation::Avx2 => is_x86_feature_detected!("avx2"),
+            #[cfg(not(target_arch = "x86_64"))]
+            _ => false,
+        }
+    }
+}
+
+/// The positions of the structural bytes of a document, in order.
+pub struct StructuralIndex {
+    positions: Vec<u32>,
+}
+
+/// Bitmaps of a 64-byte block, bit `i` standing for byte `i`.
+#[derive(Default)]
+struct Masks {
+    backslash: u64,
+    quote: u64,
+    whitespace: u64,
+    operator: u64,
+}
+
+/// The length of the largest input which can be indexed, since positions
+/// are stored as `u32`.
+pub const MAX_INPUT_LEN: usize = u32::MAX as usize;
+
+/// What stage 1 carries from one block to the next.
+#[derive(Default)]
+struct Carry {
+    /// The first byte of the next block follows an escaping backslash.
+    escaped: bool,
+    /// All ones if the previous block ended inside a string.
+    in_string: u64,
+    /// The previous block ended in the middle of a literal or number.
+    scalar: bool,
+}
+
+impl StructuralIndex {
+    /// Index `input` with the fastest available implementation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `input` is longer than `MAX_INPUT_LEN`: callers must check
+    /// its length first, as `parse` does.
+    pub fn new(input: &str) -> StructuralIndex {
+        StructuralIndex::with_implementation(input, Implementation::detect())
+    }
+
+    /// Index `input` with the given implementation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `implementation` isn't supported by the current CPU, or if
+    /// `input` is longer than `MAX_INPUT_LEN`.
+    pub fn with_implementation(input: &str, implementation: Implementation) -> StructuralIndex {
+        assert!(implementation.is_supported(),
+                "{:?} is not supported by this CPU",
+                implementation);
+        assert!(input.len() <= MAX_INPUT_LEN, "input too large to be indexed");
+
+        let bytes = input.as_bytes();
+        let mut positions = Vec::with_capacity(bytes.len() / 4);
+        let mut carry = Carry::default();
+
+        for (block_idx, chunk) in bytes.chunks(64).enumerate() {
+            let mut padded = [b' '; 64];
+            padded[..chunk.len()].copy_from_slice(chunk);
+
+            let masks = classify(&padded, implementation);
+            let structurals = find_structurals(&masks, &mut carry);
+
+            let base = (block_idx * 64) as u32;
+            let mut bits = structurals;
+
+            while bits != 0 {
+                positions.push(base + bits.trailing_zeros());
+                bits &= bits - 1;
+            }
+        }
+
+        StructuralIndex { positions: positions }
+    }
+
+    pub fn positions(&self) -> &[u32] {
+        &self.positions
+    }
+}
+
+fn classify(block: &[u8; 64], implementation: Implementation) -> Masks {
+    match implementation {
+        #[cfg(target_arch = "x86_64")]
+        Implementation::Avx2 => unsafe { classify_avx2(block) },
+        #[cfg(target_arch = "x86_64")]
+        Implementation::Sse2 => unsafe { classify_sse2(block) },
+        _ => classify_scalar(block),
+    }
+}
+
+fn classify_scalar(block: &[u8; 64]) -> Masks {
+    let mut masks = Masks::default();
+
+    for (i, &b) in block.iter().enumerate() {
+        let bit = 1u64 << i;
+
+        match b {
+            b'\\' => masks.backslash |= bit,
+            b'"' => masks.quote |= bit,
+            b' ' | b'\n' | b'\r' | b'\t' => masks.whitespace |= bit,
+            b'{' | b'}' | b'[' | b']' | b':' | b',' => masks.operator |= bit,
+            _ => {}
+        }
+    }
+
+    masks
+}
+
+// `[` and `]` only differ from `{` and `}` by the 0x20 bit, so the four
+// brackets can be matched with two comparisons once that bit is set.
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn classify_sse2(block: &[u8; 64]) -> Masks {
+    use std::arch::x86_64::*;
+
+    let mut masks = Masks::default();
+
+    for i in 0..4 {
+        // Safe because the block is 64 bytes long and `loadu` doesn't
+        // require alignment.
+        let chunk = _mm_loadu_si128(block.as_ptr().add(i * 16) as *const __m128i);
+        let folded = _mm_or_si128(chunk, _mm_set1_epi8(0x20));
+
+        macro_rules! eq {
+            ($v:expr, $b:expr) => {
+                _mm_cmpeq_epi8($v, _mm_set1_epi8($b as i8))
+            }
+        }
+        macro_rules! mask {
+            ($v:expr) => {
+                (_mm_movemask_epi8($v) as u16 as u64) << (i * 16)
+            }
+        }
+
+        masks.backslash |= mask!(eq!(chunk, b'\\'));
+        masks.quote |= mask!(eq!(chunk, b'"'));
+        masks.whitespace |= mask!(_mm_or_si128(_mm_or_si128(eq!(chunk, b' '), eq!(chunk, b'\n')),
+                                               _mm_or_si128(eq!(chunk, b'\r'), eq!(chunk, b'\t'))));
+        masks.operator |= mask!(_mm_or_si128(_mm_or_si128(eq!(folded, b'{'), eq!(folded, b'}')),
+                                             _mm_or_si128(eq!(chunk, b':'), eq!(chunk, b','))));
+    }
+
+    masks
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn classify_avx2(block: &[u8; 64]) -> Masks {
+    use std::arch::x86_64::*;
+
+    let mut masks = Masks::default();
+
+    for i in 0..2 {
+        // Safe because the block is 64 bytes long and `loadu` doesn't
+        // require alignment.
+        let chunk = _mm256_loadu_si256(block.as_ptr().add(i * 32) as *const __m256i);
+        let folded = _mm256_or_si256(chunk, _mm256_set1_epi8(0x20));
+
+        macro_rules! eq {
+            ($v:expr, $b:expr) => {
+                _mm256_cmpeq_epi8($v, _mm256_set1_epi8($b as i8))
+            }
+        }
+        macro_rules! mask {
+            ($v:expr) => {
+                (_mm256_movemask_epi8($v) as u32 as u64) << (i * 32)
+            }
+        }
+
+        masks.backslash |= mask!(eq!(chunk, b'\\'));
+        masks.quote |= mask!(eq!(chunk, b'"'));
+        masks.whitespace |= mask!(_mm256_or_si256(_mm256_or_si256(eq!(chunk, b' '),
+                                                                  eq!(chunk, b'\n')),
+                                                  _mm256_or_si256(eq!(chunk, b'\r'),
+                                                                  eq!(chunk, b'\t'))));
+        masks.operator |= mask!(_mm256_or_si256(_mm256_or_si256(eq!(folded, b'{'),
+                                                                eq!(folded, b'}')),
+                                                _mm256_or_si256(eq!(chunk, b':'),
+                                                                eq!(chunk, b','))));
+    }
+
+    masks
+}
+
+/// Bytes preceded by an escaping backslash. Backslashes are rare, so they
+/// are simply visited one by one.
+fn find_escaped(backslash: u64, carry: &mut Carry) -> u64 {
+    let mut escaped = 0;
+    let mut backslash = backslash;
+
+    if carry.escaped {
+        escaped |= 1;
+        backslash &= !1;
+    }
+
+    carry.escaped = false;
+
+    while backslash != 0 {
+        let i = backslash.trailing_zeros();
+        backslash &= !(1 << i);
+
+        if i == 63 {
+            carry.escaped = true;
+        } else {
+            escaped |= 1 << (i + 1);
+            backslash &= !(1 << (i + 1));
+        }
+    }
+
+    escaped
+}
+
+/// Bit `i` of the result is the parity of the bits `0..=i` of `x`.
+fn prefix_xor(mut x: u64) -> u64 {
+    x ^= x << 1;
+    x ^= x << 2;
+    x ^= x << 4;
+    x ^= x << 8;
+    x ^= x << 16;
+    x ^= x << 32;
+    x
+}
+
+fn find_structurals(masks: &Masks, carry: &mut Carry) -> u64 {
+    let quotes = masks.quote & !find_escaped(masks.backslash, carry);
+
+    // Set from each opening quote (included) to the closing quote (excluded).
+    let in_string = prefix_xor(quotes) ^ carry.in_string;
+    carry.in_string = ((in_string as i64) >> 63) as u64;
+
+    let operators = masks.operator & !in_string;
+
+    let scalars = !(masks.operator | masks.whitespace | masks.quote) & !in_string;
+    let scalar_starts = scalars & !((scalars << 1) | carry.scalar as u64);
+    carry.scalar = scalars >> 63 == 1;
+
+    operators | quotes | scalar_starts
+}
+
+/// Parse `input` with a structural index.
+///
+/// This accepts the same documents as `JsonParser` with the default options,
+/// and like it, ignores anything after the root value. Options can't be
+/// given: in particular, since values are built recursively, nesting is
+/// always limited to `DEFAULT_MAX_DEPTH`. Inputs longer than
+/// `MAX_INPUT_LEN` are parsed by `JsonParser`.
+pub fn parse(input: &str) -> ParseResult<JsonValue> {
+    if input.len() > MAX_INPUT_LEN {
+        return JsonParser::new(input).parse();
+    }
+
+    let index = StructuralIndex::new(input);
+    let mut builder = Builder::new(input, index.positions());
+
+    builder.parse_value()
+}
+
+/// Stage 2: reads values from the structural index.
+pub(crate) struct Builder<'input, 'i> {
+    input: &'input str,
+    positions: &'i [u32],
+    /// Index in `positions` of the next token.
+    next: usize,
+    /// Number of objects and arrays currently being parsed.
+    depth: usize,
+}
+
+impl<'input, 'i> Builder<'input, 'i> {
+    pub(crate) fn new(input: &'input str, positions: &'i [u32]) -> Builder<'input, 'i> {
+        Builder {
+            input: input,
+            positions: positions,
+            next: 0,
+            depth: 0,
+        }
+    }
+
+    /// Enter the object or array whose bracket is at `pos`, checking the
+    /// depth limit.
+    pub(crate) fn enter(&mut self, pos: usize) -> ParseResult<()> {
+        if self.depth >= DEFAULT_MAX_DEPTH {
+            return Err(ParseError::new(self.input, pos, ParseErrorKind::DepthLimitExceeded));
+        }
+
+        self.depth += 1;
+        Ok(())
+    }
+
+    pub(crate) fn leave(&mut self) {
+        self.depth -= 1;
+    }
+
+    fn peek(&self) -> Option<usize> {
+        self.positions.get(self.next).map(|&pos| pos as usize)
+    }
+
+    pub(crate) fn advance(&mut self) -> Option<usize> {
+        let pos = self.peek();
+        self.next += 1;
+        pos
+    }
+
+    pub(crate) fn peek_byte(&self) -> Option<u8> {
+        self.peek().map(|pos| self.input.as_bytes()[pos])
+    }
+
+    pub(crate) fn unexpected(&self, pos: Option<usize>, expected: Vec<char>) -> ParseError {
+        match pos {
+            Some(pos) if pos < self.input.len() => {
+                let c = self.input[pos..].chars().next().unwrap();
+                ParseError::new(self.input, pos, ParseErrorKind::UnexpectedCharacter(c, expected))
+            }
+            _ => {
+                ParseError::new(self.input,
+                                self.input.len(),
+                                ParseErrorKind::UnexpectedEndOfInput)
+            }
+        }
+    }
+
+    /// Advance to the next token, which must be `expected`.
+    pub(crate) fn expect(&mut self, expected: char) -> ParseResult<usize> {
+        match self.advance() {
+            Some(pos) if self.input.as_bytes()[pos] == expected as u8 => Ok(pos),
+            pos => Err(self.unexpected(pos, vec![expected])),
+        }
+    }
+
+    fn parse_value(&mut self) -> ParseResult<JsonValue<'input>> {
+        let pos = self.advance();
+
+        Ok(match pos.map(|pos| self.input.as_bytes()[pos]) {
+            Some(b'"') => JsonValue::String(try!(self.parse_string(pos.unwrap()))),
+            Some(b'{') => {
+                try!(self.enter(pos.unwrap()));
+                let object = try!(self.parse_object());
+                self.leave();
+                JsonValue::Object(object)
+            }
+            Some(b'[') => {
+                try!(self.enter(pos.unwrap()));
+                let array = try!(self.parse_array());
+                self.leave();
+                JsonValue::Array(array)
+            }
+            Some(b'0'...b'9') | Some(b'-') => JsonValue::Number(try!(self.parse_number(pos.unwrap()))),
+            Some(b't') => try!(self.parse_literal(pos.unwrap(), "true", JsonValue::Boolean(true))),
+            Some(b'f') => try!(self.parse_literal(pos.unwrap(), "false", JsonValue::Boolean(false))),
+            Some(b'n') => try!(self.parse_literal(pos.unwrap(), "null", JsonValue::Null)),
+            _ => return Err(self.unexpected(pos, vec!['"', '{', '[', '-', 't', 'f', 'n'])),
+        })
+    }
+
+    fn parse_object(&mut self) -> ParseResult<HashMap<Cow<'input, str>, JsonValue<'input>>> {
+        let mut members = HashMap::new();
+
+        if self.peek_byte() == Some(b'}') {
+            self.advance();
+            return Ok(members);
+        }
+
+        loop {
+            let key_start = try!(self.expect('"'));
+            let key = try!(self.parse_string(key_start));
+            try!(self.expect(':'));
+            let value = try!(self.parse_value());
+            members.insert(key, value);
+
+            match self.advance() {
+                Some(pos) if self.input.as_bytes()[pos] == b',' => {
+                    if self.peek_byte() == Some(b'}') {
+                        self.advance();
+                        return Ok(members);
+                    }
+                }
+                Some(pos) if self.input.as_bytes()[pos] == b'}' => return Ok(members),
+                pos => return Err(self.unexpected(pos, vec![',', '}'])),
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> ParseResult<Vec<JsonValue<'input>>> {
+        let mut elements = Vec::new();
+
+        if self.peek_byte() == Some(b']') {
+            self.advance();
+            return Ok(elements);
+        }
+
+        loop {
+            elements.push(try!(self.parse_value()));
+
+            match self.advance() {
+                Some(pos) if self.input.as_bytes()[pos] == b',' => {
+                    if self.peek_byte() == Some(b']') {
+                        self.advance();
+                        return Ok(elements);
+                    }
+                }
+                Some(pos) if self.input.as_bytes()[pos] == b']' => return Ok(elements),
+                pos => return Err(self.unexpected(pos, vec![',', ']'])),
+            }
+        }
+    }
+
+    /// The closing quote is always the token following the opening one.
+    pub(crate) fn parse_string(&mut self, start: usize) -> ParseResult<Cow<'input, str>> {
+        let end = match self.advance() {
+            Some(end) => end,
+            None => return Err(self.unexpected(None, vec!['"'])),
+        };
+
+        let content = &self.input[start + 1..end];
+
+        if !content.contains('\\') {
+            return Ok(Cow::Borrowed(content));
+        }
+
+        let bytes = content.as_bytes();
+        let mut buf = Vec::with_capacity(bytes.len());
+        let mut i = 0;
+
+        while i < bytes.len() {
+            if bytes[i] == b'\\' {
+                i += 1;
+                buf.push(match bytes[i] {
+                    b'"' => b'"',
+                    b'\\' => b'\\',
+                    b'n' => b'\n',
+                    b'r' => b'\r',
+                    b't' => b'\t',
+                    _ => {
+                        return Err(self.unexpected(Some(start + 1 + i),
+                                                   vec!['"', '\\', 'n', 'r', 't']))
+                    }
+                });
+            } else {
+                buf.push(bytes[i]);
+            }
+
+            i += 1;
+        }
+
+        // Only ASCII bytes were substituted, so `buf` is still UTF-8.
+        Ok(Cow::Owned(unsafe { String::from_utf8_unchecked(buf) }))
+    }
+
+    /// The bytes from `start` up to the next token, minus trailing whitespace.
+    fn scalar(&self, start: usize) -> &'input str {
+        let end = self.peek().unwrap_or(self.input.len());
+        self.input[start..end].trim_end_matches(&[' ', '\n', '\r', '\t'][..])
+    }
+
+    pub(crate) fn parse_number(&self, start: usize) -> ParseResult<f64> {
+        let number = self.scalar(start);
+        let bytes = number.as_bytes();
+        let mut i = 0;
+
+        if bytes[i] == b'-' {
+            i += 1;
+        }
+
+        let integer_start = i;
+
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+
+        if i == integer_start {
+            return Err(self.unexpected(Some(start + i), vec!['0']));
+        }
+
+        if i < bytes.len() && bytes[i] == b'.' {
+            i += 1;
+
+            if !(i < bytes.len() && bytes[i].is_ascii_digit()) {
+                return Err(self.unexpected(Some(start + i), vec!['0']));
+            }
+
+            while i < bytes.len() && bytes[i].is_ascii_digit() {
+                i += 1;
+            }
+        }
+
+        if i < bytes.len() {
+            return Err(self.unexpected(Some(start + i), vec![',']));
+        }
+
+        Ok(number.parse().unwrap())
+    }
+
+    pub(crate) fn parse_literal(&self,
+                     start: usize,
+                     literal: &str,
+                     value: JsonValue<'input>)
+                     -> ParseResult<JsonValue<'input>> {
+        let scalar = self.scalar(start);
+
+        if scalar == literal {
+            return Ok(value);
+        }
+
+        let mismatch = scalar.bytes().zip(literal.bytes()).position(|(a, b)| a != b);
+
+        Err(match mismatch {
+            Some(i) => self.unexpected(Some(start + i), vec![literal.as_bytes()[i] as char]),
+            None if scalar.len() < literal.len() => {
+                let expected = vec![literal.as_bytes()[scalar.len()] as char];
+                self.unexpected(Some(start + scalar.len()), expected)
+            }
+            None => self.unexpected(Some(start + literal.len()), vec![',']),
+        })
+    }
+}
@@ -0,0 +1,91 @@
+//! Byte scanning eight bytes at a time ("SIMD within a register").
+//!
+//! Each byte of a word is tested in parallel and the result is a word where
+//! the high bit of every matching byte is set. The tests are exact: a match
+//! in one byte never spills into its neighbours, so any bit of the result
+//! can be trusted, not just the lowest one.
+
+const ONES: u64 = 0x0101_0101_0101_0101;
+const LOW_7_BITS: u64 = 0x7f7f_7f7f_7f7f_7f7f;
+const HIGH_BITS: u64 = 0x8080_8080_8080_8080;
+
+fn load(bytes: &[u8]) -> u64 {
+    let mut word = [0; 8];
+    word.copy_from_slice(&bytes[..8]);
+    u64::from_le_bytes(word)
+}
+
+/// Bytes of `word` equal to zero.
+fn zero_bytes(word: u64) -> u64 {
+    // Adding 0x7f to the low 7 bits sets the high bit unless they are all
+    // zero, and can't carry into the next byte.
+    !(((word & LOW_7_BITS) + LOW_7_BITS) | word) & HIGH_BITS
+}
+
+/// Bytes of `word` equal to `b`.
+fn equal_bytes(word: u64, b: u8) -> u64 {
+    zero_bytes(word ^ (ONES * b as u64))
+}
+
+/// Bytes of `word` lower than `n`, which must be at most 0x80.
+fn lower_bytes(word: u64, n: u8) -> u64 {
+    !(((word & LOW_7_BITS) + ONES * (0x80 - n) as u64) | word) & HIGH_BITS
+}
+
+/// Index of the first byte of a word mask, counting from the low byte.
+fn first_byte(mask: u64) -> usize {
+    (mask.trailing_zeros() / 8) as usize
+}
+
+/// Index of the first quote, backslash or control character of `bytes`, or
+/// its length if there is none.
+pub fn find_string_special(bytes: &[u8]) -> usize {
+    let mut idx = 0;
+
+    while idx + 8 <= bytes.len() {
+        let word = load(&bytes[idx..]);
+        let special = equal_bytes(word, b'"') | equal_bytes(word, b'\\') | lower_bytes(word, 0x20);
+
+        if special != 0 {
+            return idx + first_byte(special);
+        }
+
+        idx += 8;
+    }
+
+    while idx < bytes.len() {
+        match bytes[idx] {
+            b'"' | b'\\' | 0x00...0x1f => return idx,
+            _ => idx += 1,
+        }
+    }
+
+    idx
+}
+
+/// Number of whitespace bytes at the start of `bytes`.
+pub fn whitespace_len(bytes: &[u8]) -> usize {
+    let mut idx = 0;
+
+    while idx + 8 <= bytes.len() {
+        let word = load(&bytes[idx..]);
+        let whitespace = equal_bytes(word, b' ') | equal_bytes(word, b'\n') |
+                         equal_bytes(word, b'\r') | equal_bytes(word, b'\t');
+        let other = !whitespace & HIGH_BITS;
+
+        if other != 0 {
+            return idx + first_byte(other);
+        }
+
+        idx += 8;
+    }
+
+    while idx < bytes.len() {
+        match bytes[idx] {
+            b' ' | b'\n' | b'\r' | b'\t' => idx += 1,
+            _ => return idx,
+        }
+    }
+
+    idx
+}
@@ -0,0 +1,349 @@
+//! A compact, immutable document representation.
+//!
+//! A `Tape` stores a whole document as a flat list of 64-bit entries, in
+//! document order, plus a buffer holding the content of every string. Each
+//! entry has a tag in its top 8 bits and a payload in the other 56:
+//!
+//! * `{` and `[` entries hold the index of the entry following the matching
+//!   `}` or `]` in their low 32 bits, so a container can be skipped in one
+//!   step, and the number of members or elements in the next 24 bits.
+//! * `}` and `]` entries hold the index of the matching opening entry.
+//! * `"` entries hold the offset of the string in the buffer, and are
+//!   followed by an entry containing its length in bytes.
+//! * `d` entries are followed by an entry containing the bits of an `f64`.
+//! * `t`, `f` and `n` entries stand for `true`, `false` and `null`.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use JsonValue;
+use parse_error::{ParseError, ParseErrorKind, ParseResult};
+use structural::{Builder, StructuralIndex, MAX_INPUT_LEN};
+
+const PAYLOAD_MASK: u64 = (1 << 56) - 1;
+const MAX_COUNT: u64 = (1 << 24) - 1;
+
+fn entry(tag: u8, payload: u64) -> u64 {
+    ((tag as u64) << 56) | payload
+}
+
+pub struct Tape {
+    tape: Vec<u64>,
+    strings: String,
+}
+
+impl Tape {
+    /// Parse `input` into a tape. The tape doesn't borrow from `input`.
+    ///
+    /// Inputs longer than `structural::MAX_INPUT_LEN` are rejected with
+    /// `ParseErrorKind::InputTooLarge`.
+    pub fn parse(input: &str) -> ParseResult<Tape> {
+        if input.len() > MAX_INPUT_LEN {
+            return Err(ParseError::new(input, MAX_INPUT_LEN, ParseErrorKind::InputTooLarge));
+        }
+
+        let index = StructuralIndex::new(input);
+        let mut writer = Writer {
+            input: input.as_bytes(),
+            builder: Builder::new(input, index.positions()),
+            tape: Tape {
+                tape: Vec::with_capacity(index.positions().len()),
+                strings: String::new(),
+            },
+        };
+
+        try!(writer.write_value());
+        writer.tape.tape.shrink_to_fit();
+        writer.tape.strings.shrink_to_fit();
+
+        Ok(writer.tape)
+    }
+
+    pub fn root(&self) -> Cursor {
+        Cursor {
+            tape: self,
+            idx: 0,
+        }
+    }
+
+    /// Number of bytes allocated by the tape.
+    pub fn heap_size(&self) -> usize {
+        self.tape.capacity() * 8 + self.strings.capacity()
+    }
+}
+
+struct Writer<'input, 'i> {
+    input: &'input [u8],
+    builder: Builder<'input, 'i>,
+    tape: Tape,
+}
+
+impl<'input, 'i> Writer<'input, 'i> {
+    fn write_value(&mut self) -> ParseResult<()> {
+        let pos = self.builder.advance();
+
+        match pos.map(|pos| self.input[pos]) {
+            Some(b'"') => {
+                let string = try!(self.builder.parse_string(pos.unwrap()));
+                self.write_string(&string);
+            }
+            Some(b'{') => {
+                try!(self.builder.enter(pos.unwrap()));
+                try!(self.write_container(b'{', b'}'));
+                self.builder.leave();
+            }
+            Some(b'[') => {
+                try!(self.builder.enter(pos.unwrap()));
+                try!(self.write_container(b'[', b']'));
+                self.builder.leave();
+            }
+            Some(b'0'...b'9') | Some(b'-') => {
+                let number = try!(self.builder.parse_number(pos.unwrap()));
+                self.tape.tape.push(entry(b'd', 0));
+                self.tape.tape.push(number.to_bits());
+            }
+            Some(b't') => {
+                try!(self.builder.parse_literal(pos.unwrap(), "true", JsonValue::Boolean(true)));
+                self.tape.tape.push(entry(b't', 0));
+            }
+            Some(b'f') => {
+                try!(self.builder.parse_literal(pos.unwrap(), "false", JsonValue::Boolean(false)));
+                self.tape.tape.push(entry(b'f', 0));
+            }
+            Some(b'n') => {
+                try!(self.builder.parse_literal(pos.unwrap(), "null", JsonValue::Null));
+                self.tape.tape.push(entry(b'n', 0));
+            }
+            _ => return Err(self.builder.unexpected(pos, vec!['"', '{', '[', '-', 't', 'f', 'n'])),
+        }
+
+        Ok(())
+    }
+
+    fn write_string(&mut self, string: &str) {
+        self.tape.tape.push(entry(b'"', self.tape.strings.len() as u64));
+        self.tape.tape.push(string.len() as u64);
+        self.tape.strings.push_str(string);
+    }
+
+    fn write_container(&mut self, open: u8, close: u8) -> ParseResult<()> {
+        let start = self.tape.tape.len();
+        let mut count = 0;
+        self.tape.tape.push(0);
+
+        if self.builder.peek_byte() == Some(close) {
+            self.builder.advance();
+        } else {
+            loop {
+                if open == b'{' {
+                    let key_start = try!(self.builder.expect('"'));
+                    let key = try!(self.builder.parse_string(key_start));
+                    self.write_string(&key);
+                    try!(self.builder.expect(':'));
+                }
+
+                try!(self.write_value());
+                count += 1;
+
+                match self.builder.advance() {
+                    Some(pos) if self.input[pos] == b',' => {
+                        if self.builder.peek_byte() == Some(close) {
+                            self.builder.advance();
+                            break;
+                        }
+                    }
+                    Some(pos) if self.input[pos] == close => break,
+                    pos => return Err(self.builder.unexpected(pos, vec![',', close as char])),
+                }
+            }
+        }
+
+        self.tape.tape.push(entry(close, start as u64));
+        let end = self.tape.tape.len() as u64;
+        self.tape.tape[start] = entry(open, (count.min(MAX_COUNT) << 32) | end);
+
+        Ok(())
+    }
+}
+
+/// A position in a `Tape`, standing for the value which starts there.
+#[derive(Clone, Copy)]
+pub struct Cursor<'t> {
+    tape: &'t Tape,
+    idx: usize,
+}
+
+impl<'t> Cursor<'t> {
+    fn tag(&self) -> u8 {
+        (self.tape.tape[self.idx] >> 56) as u8
+    }
+
+    fn payload(&self) -> u64 {
+        self.tape.tape[self.idx] & PAYLOAD_MASK
+    }
+
+    /// Index of the entry following this value.
+    fn end(&self) -> usize {
+        match self.tag() {
+            b'{' | b'[' => (self.payload() & 0xffff_ffff) as usize,
+            b'"' | b'd' => self.idx + 2,
+            _ => self.idx + 1,
+        }
+    }
+
+    fn at_idx(&self, idx: usize) -> Cursor<'t> {
+        Cursor {
+            tape: self.tape,
+            idx: idx,
+        }
+    }
+
+    pub fn is_object(&self) -> bool {
+        self.tag() == b'{'
+    }
+
+    pub fn is_array(&self) -> bool {
+        self.tag() == b'['
+    }
+
+    pub fn is_null(&self) -> bool {
+        self.tag() == b'n'
+    }
+
+    pub fn as_str(&self) -> Option<&'t str> {
+        if self.tag() != b'"' {
+            return None;
+        }
+
+        let start = self.payload() as usize;
+        let len = self.tape.tape[self.idx + 1] as usize;
+        Some(&self.tape.strings[start..start + len])
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self.tag() {
+            b'd' => Some(f64::from_bits(self.tape.tape[self.idx + 1])),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.tag() {
+            b't' => Some(true),
+            b'f' => Some(false),
+            _ => None,
+        }
+    }
+
+    /// The number of members of an object or elements of an array.
+    pub fn len(&self) -> Option<usize> {
+        match self.tag() {
+            b'{' | b'[' => {
+                let count = (self.payload() >> 32) & MAX_COUNT;
+
+                if count < MAX_COUNT {
+                    Some(count as usize)
+                } else {
+                    Some(if self.is_object() {
+                        self.members().count()
+                    } else {
+                        self.elements().count()
+                    })
+                }
+            }
+            _ => None,
+        }
+    }
+
+    pub fn is_empty(&self) -> Option<bool> {
+        self.len().map(|len| len == 0)
+    }
+
+    /// Look up the member `key` of an object. If the key is duplicated, the
+    /// last member wins, as when parsing into a `JsonValue`.
+    pub fn get(&self, key: &str) -> Option<Cursor<'t>> {
+        self.members().filter(|&(k, _)| k == key).last().map(|(_, value)| value)
+    }
+
+    /// The element at `idx` of an array.
+    pub fn at(&self, idx: usize) -> Option<Cursor<'t>> {
+        self.elements().nth(idx)
+    }
+
+    /// The elements of an array, or nothing if this isn't an array.
+    pub fn elements(&self) -> Elements<'t> {
+        Elements {
+            cursor: self.at_idx(self.idx + 1),
+            end: if self.is_array() { self.end() - 1 } else { self.idx + 1 },
+        }
+    }
+
+    /// The members of an object, or nothing if this isn't an object.
+    pub fn members(&self) -> Members<'t> {
+        Members {
+            cursor: self.at_idx(self.idx + 1),
+            end: if self.is_object() { self.end() - 1 } else { self.idx + 1 },
+        }
+    }
+
+    /// Build the `JsonValue` of this value. Strings borrow from the tape.
+    pub fn to_json_value(&self) -> JsonValue<'t> {
+        match self.tag() {
+            b'{' => {
+                let mut members = HashMap::with_capacity(self.len().unwrap());
+
+                for (key, value) in self.members() {
+                    members.insert(Cow::Borrowed(key), value.to_json_value());
+                }
+
+                JsonValue::Object(members)
+            }
+            b'[' => JsonValue::Array(self.elements().map(|value| value.to_json_value()).collect()),
+            b'"' => JsonValue::String(Cow::Borrowed(self.as_str().unwrap())),
+            b'd' => JsonValue::Number(self.as_f64().unwrap()),
+            b't' => JsonValue::Boolean(true),
+            b'f' => JsonValue::Boolean(false),
+            _ => JsonValue::Null,
+        }
+    }
+}
+
+pub struct Elements<'t> {
+    cursor: Cursor<'t>,
+    /// Index of the closing entry.
+    end: usize,
+}
+
+impl<'t> Iterator for Elements<'t> {
+    type Item = Cursor<'t>;
+
+    fn next(&mut self) -> Option<Cursor<'t>> {
+        if self.cursor.idx >= self.end {
+            return None;
+        }
+
+        let element = self.cursor;
+        self.cursor = element.at_idx(element.end());
+        Some(element)
+    }
+}
+
+pub struct Members<'t> {
+    cursor: Cursor<'t>,
+    /// Index of the closing entry.
+    end: usize,
+}
+
+impl<'t> Iterator for Members<'t> {
+    type Item = (&'t str, Cursor<'t>);
+
+    fn next(&mut self) -> Option<(&'t str, Cursor<'t>)> {
+        if self.cursor.idx >= self.end {
+            return None;
+        }
+
+        let key = self.cursor.as_str().unwrap();
+        let value = self.cursor.at_idx(self.cursor.end());
+        self.cursor = value.at_idx(value.end());
+        Some((key, value))
+    }
+}
@@ -0,0 +1,61 @@
+extern crate json_parser;
+use json_parser::*;
+use json_parser::arena::*;
+
+const MESSAGE: &'static str = r##"{"id": 12, "user": "a \"quoted\" name", "tags": ["x", "y"], "ok": true, "extra": null}"##;
+
+#[test]
+fn arena_values() {
+  let arena = Bump::new();
+  let value = parse_in(MESSAGE, &arena).unwrap();
+
+  assert_eq!(value.get("id"), Some(&ArenaValue::Number(12.0)));
+  assert_eq!(value.get("user"), Some(&ArenaValue::String("a \"quoted\" name")));
+  assert_eq!(value.get("tags"), Some(&ArenaValue::Array(&[ArenaValue::String("x"), ArenaValue::String("y")])));
+  assert_eq!(value.get("missing"), None);
+
+  assert_eq!(value.to_json_value(), parse_json(MESSAGE).unwrap());
+}
+
+#[test]
+fn strings_borrow_from_input_or_arena() {
+  let input = r##"["plain", "esc\taped"]"##;
+  let arena = Bump::new();
+  let value = parse_in(input, &arena).unwrap();
+
+  match value {
+    ArenaValue::Array(&[ArenaValue::String(plain), ArenaValue::String(escaped)]) => {
+      assert!(input.as_bytes().as_ptr_range().contains(&plain.as_ptr()));
+      assert_eq!(escaped, "esc\taped");
+      assert!(!input.as_bytes().as_ptr_range().contains(&escaped.as_ptr()));
+    }
+    _ => panic!("unexpected value {:?}", value),
+  }
+}
+
+#[test]
+fn reused_arena() {
+  let mut arena = Bump::new();
+
+  for _ in 0..1000 {
+    {
+      let value = parse_in(MESSAGE, &arena).unwrap();
+      assert_eq!(value.get("ok"), Some(&ArenaValue::Boolean(true)));
+    }
+    arena.reset();
+  }
+
+  // The arena keeps its largest chunk and reuses it.
+  assert!(arena.allocated_bytes() < 4096);
+}
+
+#[test]
+fn invalid_documents() {
+  let arena = Bump::new();
+
+  assert!(parse_in("[1, 2", &arena).is_err());
+  assert!(parse_in("{\"a\" 1}", &arena).is_err());
+  assert!(parse_in("[\"\\x\"]", &arena).is_err());
+  assert!(parse_in("[?]", &arena).is_err());
+  assert!(parse_in("{\"a\"", &arena).is_err());
+}
@@ -0,0 +1,149 @@
+extern crate json_parser;
+use json_parser::*;
+use std::thread;
+
+const DEPTH: usize = 200000;
+
+fn deep(depth: usize) -> JsonValue<'static> {
+  let mut value = JsonValue::Null;
+
+  for i in 0..depth {
+    value = if i % 2 == 0 {
+      JsonValue::Array(vec![value, JsonValue::Number(i as f64)])
+    } else {
+      JsonValue::Object(vec![("a".into(), value)].into_iter().collect())
+    };
+  }
+
+  value
+}
+
+// Run `f` on a small stack, so any recursion on the depth would overflow it.
+fn on_small_stack<F: FnOnce() + Send + 'static>(f: F) {
+  thread::Builder::new().stack_size(64 * 1024).spawn(f).unwrap().join().unwrap();
+}
+
+#[test]
+fn drop_and_clone() {
+  on_small_stack(|| {
+    let value = deep(DEPTH);
+    let copy = value.clone();
+    drop(value);
+    drop(copy);
+
+    let input = format!("{}{}", "[".repeat(DEPTH), "]".repeat(DEPTH));
+    let options = ParserOptions { max_depth: usize::MAX, ..ParserOptions::default() };
+    drop(JsonParser::with_options(&input, options).parse().unwrap());
+  });
+}
+
+#[test]
+fn compare() {
+  on_small_stack(|| {
+    let value = deep(DEPTH);
+    assert!(value == value.clone());
+    assert!(value != deep(DEPTH - 1));
+
+    let mut other = deep(DEPTH - 1);
+    if let JsonValue::Object(ref mut members) = other {
+      let inner = members.remove("a").unwrap();
+      members.insert("b".into(), inner);
+    }
+    assert!(deep(DEPTH) != JsonValue::Array(vec![other, JsonValue::Number((DEPTH - 1) as f64)]));
+  });
+}
+
+#[test]
+fn debug() {
+  on_small_stack(|| {
+    let debug = format!("{:?}", deep(10000));
+    assert!(debug.starts_with("Object({\"a\": Array([Object({\"a\": Array(["));
+    assert!(debug.ends_with("Number(9998.0)])})"));
+    assert!(format!("{:#?}", deep(1000)).ends_with("        ),\n    },\n)"));
+  });
+}
+
+#[test]
+fn debug_format() {
+  let value = parse_json(r#"[{"a": [1.5, "x\"y", true, null, [], {}, [[2]]]}]"#).unwrap();
+  assert_eq!(format!("{:?}", value),
+             r#"Array([Object({"a": Array([Number(1.5), String("x\"y"), Boolean(true), Null, Array([]), Object({}), Array([Array([Number(2.0)])])])})])"#);
+  assert_eq!(format!("{:#?}", value), r#"Array(
+    [
+        Object(
+            {
+                "a": Array(
+                    [
+                        Number(
+                            1.5,
+                        ),
+                        String(
+                            "x\"y",
+                        ),
+                        Boolean(
+                            true,
+                        ),
+                        Null,
+                        Array(
+                            [],
+                        ),
+                        Object(
+                            {},
+                        ),
+                        Array(
+                            [
+                                Array(
+                                    [
+                                        Number(
+                                            2.0,
+                                        ),
+                                    ],
+                                ),
+                            ],
+                        ),
+                    ],
+                ),
+            },
+        ),
+    ],
+)"#);
+
+  let options = ParserOptions { lazy_depth: Some(2), ..ParserOptions::default() };
+  let value = JsonParser::with_options(r#"[{"a": [1, {}]}, false]"#, options).parse().unwrap();
+  assert_eq!(format!("{:?}", value),
+             r#"Array([Object({"a": Raw(RawValue { json: "[1, {}]" })}), Boolean(false)])"#);
+  assert_eq!(format!("{:#?}", value), r#"Array(
+    [
+        Object(
+            {
+                "a": Raw(
+                    RawValue {
+                        json: "[1, {}]",
+                    },
+                ),
+            },
+        ),
+        Boolean(
+            false,
+        ),
+    ],
+)"#);
+}
+
+#[test]
+fn move_out_of_variants() {
+  let value = parse_json(r#"{"a": ["b", [1, {}]], "c": "d"}"#).unwrap();
+  let mut members = value.into_object().unwrap();
+  let mut elements = members.remove("a").unwrap().into_array().unwrap();
+
+  assert_eq!(elements.len(), 2);
+  assert_eq!(elements.remove(0).into_string().unwrap(), "b");
+  assert_eq!(members.remove("c").unwrap().into_string().unwrap(), "d");
+
+  assert_eq!(JsonValue::Null.into_string(), None);
+  assert_eq!(JsonValue::String("x".into()).into_array(), None);
+  assert_eq!(JsonValue::Array(Vec::new()).into_object(), None);
+
+  // Deep values moved out are still dropped without recursion.
+  on_small_stack(|| drop(JsonValue::Array(vec![deep(DEPTH)]).into_array().unwrap()));
+}
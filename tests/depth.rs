@@ -0,0 +1,76 @@
+extern crate json_parser;
+use json_parser::*;
+use std::thread;
+
+fn nested(depth: usize) -> String {
+  format!("{}1{}", "[".repeat(depth), "]".repeat(depth))
+}
+
+fn is_depth_error<T>(result: Result<T, ParseError>) -> bool {
+  match result {
+    Err(e) => format!("{:?}", e).contains("DepthLimitExceeded"),
+    Ok(_) => false,
+  }
+}
+
+#[test]
+fn pathological_nesting() {
+  let input = "[".repeat(100000);
+
+  assert!(is_depth_error(parse_json(&input)));
+  assert!(is_depth_error(parse_json(&"{\"a\":".repeat(100000))));
+  assert!(is_depth_error(structural::parse(&input)));
+  assert!(is_depth_error(tape::Tape::parse(&input)));
+  assert!(is_depth_error(arena::parse_in(&input, &arena::Bump::new())));
+
+  let lazy = ParserOptions { lazy_depth: Some(1), ..ParserOptions::default() };
+  assert!(is_depth_error(JsonParser::with_options(&input, lazy).parse()));
+
+  match extract(&input, &["/0/0"]) {
+    Err(ExtractError::Parse(e)) => assert!(format!("{:?}", e).contains("DepthLimitExceeded")),
+    other => panic!("unexpected result {:?}", other),
+  };
+}
+
+#[test]
+fn default_limit() {
+  assert!(parse_json(&nested(DEFAULT_MAX_DEPTH)).is_ok());
+  assert!(is_depth_error(parse_json(&nested(DEFAULT_MAX_DEPTH + 1))));
+  assert!(structural::parse(&nested(DEFAULT_MAX_DEPTH)).is_ok());
+  assert!(is_depth_error(structural::parse(&nested(DEFAULT_MAX_DEPTH + 1))));
+}
+
+#[test]
+fn configurable_limit() {
+  let options = ParserOptions { max_depth: 2, ..ParserOptions::default() };
+
+  assert!(JsonParser::with_options("[{\"a\": 1}]", options.clone()).parse().is_ok());
+  assert!(is_depth_error(JsonParser::with_options("[{\"a\": []}]", options.clone()).parse()));
+
+  let options = ParserOptions { max_depth: 300, ..ParserOptions::default() };
+  assert!(JsonParser::with_options(&nested(300), options).parse().is_ok());
+}
+
+#[test]
+fn constant_stack_usage() {
+  // Parse on a small stack, so any recursion on the depth would overflow it.
+  let child = thread::Builder::new().stack_size(64 * 1024).spawn(|| {
+    let input = nested(1000000);
+    let options = ParserOptions { max_depth: usize::MAX, ..ParserOptions::default() };
+
+    assert!(JsonParser::with_options(&input, options.clone()).parse().is_ok());
+
+    let lazy = ParserOptions { lazy_depth: Some(1), ..options };
+    match JsonParser::with_options(&input, lazy).parse().unwrap() {
+      JsonValue::Array(ref elements) => {
+        match elements[0] {
+          JsonValue::Raw(ref raw) => assert_eq!(raw.get().len(), input.len() - 2),
+          ref other => panic!("unexpected element {:?}", other),
+        }
+      }
+      ref other => panic!("unexpected value {:?}", other),
+    };
+  });
+
+  child.unwrap().join().unwrap();
+}
@@ -0,0 +1,73 @@
+extern crate json_parser;
+use json_parser::*;
+use json_parser::diagnostic::{render, RenderOptions};
+
+fn render_error(input: &str, options: RenderOptions) -> String {
+  let error = parse_json(input).unwrap_err();
+  render(input, &error, options).to_string()
+}
+
+fn hint(input: &str) -> Option<String> {
+  let rendered = render_error(input, RenderOptions::default());
+  rendered.lines().find(|line| line.contains("= help:")).map(|line| line.split("= help: ").nth(1).unwrap().to_string())
+}
+
+#[test]
+fn snippet() {
+  let input = "{\n  \"a\": 1,\n  \"b\": 2 \"c\": 3\n}";
+
+  assert_eq!(render_error(input, RenderOptions::default()),
+             "error: expected ',' or '}', found '\"'
+ --> line 3, column 10
+  |
+1 | {
+2 |   \"a\": 1,
+3 |   \"b\": 2 \"c\": 3
+  |          ^ expected ',' or '}'
+4 | }
+  |
+  = help: a ',' may be missing before this value
+");
+}
+
+#[test]
+fn context_lines() {
+  let input = (1..20).map(|i| format!("{},", i)).collect::<Vec<_>>().join("\n");
+  let input = format!("[{}\n]", input.replace("12,", "12,,"));
+  let options = RenderOptions { context_lines: 1, ..RenderOptions::default() };
+
+  assert_eq!(render_error(&input, options),
+             "error: expected '\"', '{', '[', '-', 't', 'f' or 'n', found ','
+  --> /12, line 12, column 4
+   |
+11 | 11,
+12 | 12,,
+   |    ^ expected '\"', '{', '[', '-', 't', 'f' or 'n'
+13 | 13,
+   |
+");
+
+  let input = "[1,\n\ttrue\t?]";
+  assert!(render_error(input, RenderOptions::default()).contains("2 | \ttrue\t?]\n  | \t    \t^ expected"));
+}
+
+#[test]
+fn hints() {
+  assert_eq!(hint("{'a': 1}").unwrap(), "strings must be enclosed in double quotes");
+  assert_eq!(hint("{\"a\": 'b'}").unwrap(), "strings must be enclosed in double quotes");
+  assert_eq!(hint("{a: 1}").unwrap(), "object keys must be strings in double quotes");
+  assert_eq!(hint("[1, True]").unwrap(), "use `true` instead of `True`");
+  assert_eq!(hint("{\"a\": None}").unwrap(), "use `null` instead of `None`");
+  assert_eq!(hint("[1 2]").unwrap(), "a ',' may be missing before this value");
+  assert_eq!(hint("[1, 2"), None);
+  assert_eq!(hint("[1, x]"), None);
+}
+
+#[test]
+fn color() {
+  let options = RenderOptions { color: true, ..RenderOptions::default() };
+  let rendered = render_error("[1 }", options);
+
+  assert!(rendered.starts_with("\x1b[1;31merror\x1b[0m\x1b[1m: expected ',' or ']', found '}'\x1b[0m\n"));
+  assert!(rendered.contains("\x1b[1;34m1 |\x1b[0m [1 }\n\x1b[1;34m  |\x1b[0m    \x1b[1;31m^ expected ',' or ']'\x1b[0m\n"));
+}
@@ -0,0 +1,84 @@
+extern crate json_parser;
+use json_parser::*;
+use json_parser::diff::*;
+use json_parser::patch::apply_patch;
+use std::borrow::Cow;
+
+#[test]
+fn changes() {
+  let old = parse_json(r##"{"name": "Alice", "age": 30, "tags": ["a", "b", "c"], "a/b": {"x": 1}}"##).unwrap();
+  let new = parse_json(r##"{"name": "Alice", "age": "thirty", "tags": ["a", "d"], "a/b": {"x": 2}, "email": null}"##).unwrap();
+
+  assert_eq!(diff(&old, &new),
+             vec![Change::Changed {
+                    path: "/a~1b/x".to_string(),
+                    old: &JsonValue::Number(1.0),
+                    new: &JsonValue::Number(2.0),
+                  },
+                  Change::TypeChanged {
+                    path: "/age".to_string(),
+                    old: &JsonValue::Number(30.0),
+                    new: &JsonValue::String(Cow::Borrowed("thirty")),
+                  },
+                  Change::Added { path: "/email".to_string(), value: &JsonValue::Null },
+                  Change::Changed {
+                    path: "/tags/1".to_string(),
+                    old: &JsonValue::String(Cow::Borrowed("b")),
+                    new: &JsonValue::String(Cow::Borrowed("d")),
+                  },
+                  Change::Removed {
+                    path: "/tags/2".to_string(),
+                    value: &JsonValue::String(Cow::Borrowed("c")),
+                  }]);
+
+  assert!(diff(&old, &old).is_empty());
+}
+
+#[test]
+fn patch_round_trip() {
+  let old = parse_json(r##"{"a": [1, 2, 3, 4, 5], "b": {"c": [true, {"d": null}]}, "e": "f"}"##).unwrap();
+  let new = parse_json(r##"{"a": [1, 3], "b": {"c": [false, {"d": 1}, 7, 8]}, "g": {}}"##).unwrap();
+
+  let mut patched = old.clone();
+  apply_patch(&mut patched, &to_patch(&diff(&old, &new))).unwrap();
+  assert_eq!(patched, new);
+}
+
+#[test]
+fn unordered_arrays() {
+  let old = parse_json(r##"{"tags": ["a", "b", "c", "b"]}"##).unwrap();
+  let new = parse_json(r##"{"tags": ["c", "b", "a", "d"]}"##).unwrap();
+  let options = DiffOptions { arrays: ArrayMode::Unordered };
+
+  assert_eq!(diff_with_options(&old, &new, &options),
+             vec![Change::Removed {
+                    path: "/tags/3".to_string(),
+                    value: &JsonValue::String(Cow::Borrowed("b")),
+                  },
+                  Change::Added {
+                    path: "/tags/-".to_string(),
+                    value: &JsonValue::String(Cow::Borrowed("d")),
+                  }]);
+}
+
+#[test]
+fn arrays_matched_by_id() {
+  let old = parse_json(r##"[{"id": 1, "v": "a"}, {"id": 2, "v": "b"}, {"id": 3, "v": "c"}]"##).unwrap();
+  let new = parse_json(r##"[{"id": 3, "v": "c"}, {"id": 4, "v": "d"}, {"id": 1, "v": "x"}]"##).unwrap();
+  let options = DiffOptions { arrays: ArrayMode::ById("id".to_string()) };
+  let changes = diff_with_options(&old, &new, &options);
+
+  assert_eq!(changes,
+             vec![Change::Changed {
+                    path: "/0/v".to_string(),
+                    old: &JsonValue::String(Cow::Borrowed("a")),
+                    new: &JsonValue::String(Cow::Borrowed("x")),
+                  },
+                  Change::Removed { path: "/1".to_string(), value: &old.pointer("/1").unwrap() },
+                  Change::Added { path: "/-".to_string(), value: &new.pointer("/1").unwrap() }]);
+
+  let mut patched = old.clone();
+  apply_patch(&mut patched, &to_patch(&changes)).unwrap();
+  assert_eq!(patched,
+             parse_json(r##"[{"id": 1, "v": "x"}, {"id": 3, "v": "c"}, {"id": 4, "v": "d"}]"##).unwrap());
+}
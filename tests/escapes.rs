@@ -24,3 +24,26 @@ fn various_escapes() {
 
   assert_eq!(parse_json(input).unwrap(), expected);
 }
+
+#[test]
+fn escapes_across_words() {
+  // Put the escapes and multi-byte characters at every offset relative to
+  // an 8-byte boundary.
+  for padding in 0..17 {
+    let content = format!("{}é\\\"ß\\\\{}\\n漢字\\t", "x".repeat(padding), "y".repeat(padding));
+    let input = format!("[{}\"{}\" ,\"{}\"]", " ".repeat(padding), content, "z".repeat(padding));
+
+    let expected = format!("{}é\"ß\\{}\n漢字\t", "x".repeat(padding), "y".repeat(padding));
+    let expected = JsonValue::Array(vec![JsonValue::String(Cow::Owned(expected)),
+                                         JsonValue::String(Cow::Owned("z".repeat(padding)))]);
+
+    assert_eq!(parse_json(&input).unwrap(), expected);
+  }
+}
+
+#[test]
+fn invalid_escapes() {
+  assert!(parse_json(r##"["abc\x"]"##).is_err());
+  assert!(parse_json(r##"["abcdefghijklmnop"##).is_err());
+  assert!(parse_json(r##"["abc\"##).is_err());
+}
@@ -0,0 +1,66 @@
+extern crate json_parser;
+use json_parser::*;
+use std::borrow::Cow;
+
+const INPUT: &'static str = r##"{
+  "id": "0001",
+  "friends": [
+    {"name": "Alice", "tags": ["a", "b"]},
+    {"name": "Bob", "tags": []},
+    {"name": "Carol", "address": {"city": "Paris", "zip": "75001"}}
+  ],
+  "a/b": 1.5,
+  "nested": {"deep": {"deeper": [true, false, null]}}
+}"##;
+
+#[test]
+fn extract_values() {
+  let values = extract(INPUT,
+                       &["/friends/2/name", "/id", "/friends/2/address", "/friends/2/address/city",
+                         "/a~1b", "/nested/deep/deeper/1", "/missing", "/friends/3", "/id/x"])
+                 .unwrap();
+
+  assert_eq!(values,
+             vec![Some(JsonValue::String(Cow::Borrowed("Carol"))),
+                  Some(JsonValue::String(Cow::Borrowed("0001"))),
+                  Some(parse_json(r##"{"city": "Paris", "zip": "75001"}"##).unwrap()),
+                  Some(JsonValue::String(Cow::Borrowed("Paris"))),
+                  Some(JsonValue::Number(1.5)),
+                  Some(JsonValue::Boolean(false)),
+                  None,
+                  None,
+                  None]);
+}
+
+#[test]
+fn extraction_stops_once_everything_is_found() {
+  // Everything after the first element is garbage, but is never looked at.
+  let values = extract(r##"[{"id": 7}, [1, 2 3] GARBAGE"##, &["/0/id"]).unwrap();
+  assert_eq!(values, vec![Some(JsonValue::Number(7.0))]);
+
+  // The rest of an object may contain a duplicate key, so it is scanned.
+  assert!(extract(r##"{"id": 7, "rest": [1, 2 3] GARBAGE"##, &["/id"]).is_err());
+
+  // Skipped subtrees before the requested value are still validated.
+  assert!(extract(r##"{"rest": [1, 2 3], "id": 7}"##, &["/id"]).is_err());
+}
+
+#[test]
+fn duplicate_keys() {
+  let input = r##"{"a": 1, "b": {"c": 2, "d": 3}, "a": 4, "b": {"c": 5}}"##;
+  let pointers = ["/a", "/b/c", "/b/d", "/b"];
+  let doc = parse_json(input).unwrap();
+
+  let expected: Vec<Option<JsonValue>> = pointers.iter().map(|p| doc.pointer(p).ok().cloned()).collect();
+  assert_eq!(extract(input, &pointers).unwrap(), expected);
+  assert_eq!(expected[0], Some(JsonValue::Number(4.0)));
+  assert_eq!(expected[2], None);
+}
+
+#[test]
+fn invalid_pointer() {
+  match extract(INPUT, &["/id", "id"]) {
+    Err(ExtractError::InvalidPointer(1, PointerError::InvalidSyntax)) => {}
+    other => panic!("unexpected result: {:?}", other),
+  }
+}
@@ -0,0 +1,65 @@
+extern crate json_parser;
+use json_parser::*;
+use std::borrow::Cow;
+
+fn keys<'a>(value: &'a JsonValue) -> Vec<&'a Cow<'a, str>> {
+  match *value {
+    JsonValue::Object(ref members) => members.keys().collect(),
+    _ => panic!("not an object"),
+  }
+}
+
+#[test]
+fn keys_are_shared_across_documents() {
+  let interner = Interner::new();
+  let lines = vec![r##"{"id": 1, "na\tme": "a"}"##, r##"{"id": 2, "na\tme": "b"}"##];
+
+  let values: Vec<JsonValue> = lines.iter()
+                                    .map(|line| {
+                                      JsonParser::with_interner(line, ParserOptions::default(), &interner)
+                                        .parse()
+                                        .unwrap()
+                                    })
+                                    .collect();
+
+  assert_eq!(values[0], parse_json(lines[0]).unwrap());
+
+  for key in keys(&values[0]) {
+    let same_key = keys(&values[1]).into_iter().find(|k| *k == key).unwrap();
+
+    match (key, same_key) {
+      (&Cow::Borrowed(a), &Cow::Borrowed(b)) => assert_eq!(a.as_ptr(), b.as_ptr()),
+      _ => panic!("keys should borrow from the interner"),
+    }
+  }
+
+  assert_eq!(interner.len(), 2);
+  assert_eq!(interner.resolve(interner.get("na\tme").unwrap()), "na\tme");
+}
+
+#[test]
+fn symbols() {
+  let interner = Interner::new();
+  let a = interner.intern("a");
+  let b = interner.intern("b");
+
+  assert!(a != b);
+  assert_eq!(interner.intern("a"), a);
+  assert_eq!(interner.resolve(b), "b");
+  assert_eq!(interner.get("c"), None);
+}
+
+#[test]
+fn stats() {
+  let interner = Interner::new();
+  assert_eq!(interner.stats().hit_rate(), 0.0);
+
+  for _ in 0..10 {
+    let line = r##"{"a": 1, "b": {"a": [{"c": null}]}}"##;
+    JsonParser::with_interner(line, ParserOptions::default(), &interner).parse().unwrap();
+  }
+
+  let stats = interner.stats();
+  assert_eq!(stats, InternerStats { symbols: 3, lookups: 40, hits: 37 });
+  assert_eq!(stats.hit_rate(), 37.0 / 40.0);
+}
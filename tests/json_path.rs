@@ -0,0 +1,132 @@
+extern crate json_parser;
+use json_parser::*;
+use json_parser::json_path::*;
+
+// The example document from RFC 9535, section 1.5.
+const BOOKSTORE: &'static str = r##"{ "store": {
+    "book": [
+      { "category": "reference",
+        "author": "Nigel Rees",
+        "title": "Sayings of the Century",
+        "price": 8.95
+      },
+      { "category": "fiction",
+        "author": "Evelyn Waugh",
+        "title": "Sword of Honour",
+        "price": 12.99
+      },
+      { "category": "fiction",
+        "author": "Herman Melville",
+        "title": "Moby Dick",
+        "isbn": "0-553-21311-3",
+        "price": 8.99
+      },
+      { "category": "fiction",
+        "author": "J. R. R. Tolkien",
+        "title": "The Lord of the Rings",
+        "isbn": "0-395-19395-8",
+        "price": 22.99
+      }
+    ],
+    "bicycle": {
+      "color": "red",
+      "price": 399
+    }
+  }
+}"##;
+
+fn paths(query: &str, input: &str) -> Vec<String> {
+  let doc = parse_json(input).unwrap();
+  let mut paths: Vec<String> = JsonPath::parse(query).unwrap()
+                                                     .query(&doc)
+                                                     .into_iter()
+                                                     .map(|m| m.path)
+                                                     .collect();
+  paths.sort();
+  paths
+}
+
+#[test]
+fn rfc_bookstore_examples() {
+  assert_eq!(paths("$.store.book[*].author", BOOKSTORE),
+             vec!["$['store']['book'][0]['author']",
+                  "$['store']['book'][1]['author']",
+                  "$['store']['book'][2]['author']",
+                  "$['store']['book'][3]['author']"]);
+  assert_eq!(paths("$..author", BOOKSTORE).len(), 4);
+  assert_eq!(paths("$.store.*", BOOKSTORE), vec!["$['store']['bicycle']", "$['store']['book']"]);
+  assert_eq!(paths("$.store..price", BOOKSTORE).len(), 5);
+  assert_eq!(paths("$..book[2]", BOOKSTORE), vec!["$['store']['book'][2]"]);
+  assert_eq!(paths("$..book[-1]", BOOKSTORE), vec!["$['store']['book'][3]"]);
+  assert_eq!(paths("$..book[0,1]", BOOKSTORE), paths("$..book[:2]", BOOKSTORE));
+  assert_eq!(paths("$..book[?@.isbn]", BOOKSTORE),
+             vec!["$['store']['book'][2]", "$['store']['book'][3]"]);
+  assert_eq!(paths("$..book[?@.price<10]", BOOKSTORE),
+             vec!["$['store']['book'][0]", "$['store']['book'][2]"]);
+  assert_eq!(paths("$..*", BOOKSTORE).len(), 27);
+}
+
+#[test]
+fn filter_values() {
+  let doc = parse_json(BOOKSTORE).unwrap();
+  let query = JsonPath::parse("$.store.book[?@.price < 10].title").unwrap();
+  let titles: Vec<&JsonValue> = query.query(&doc).into_iter().map(|m| m.value).collect();
+
+  assert_eq!(titles,
+             vec![doc.pointer("/store/book/0/title").unwrap(),
+                  doc.pointer("/store/book/2/title").unwrap()]);
+
+  assert_eq!(paths("$.store.book[?@.category == 'fiction' && !(@.price > 20)]", BOOKSTORE),
+             vec!["$['store']['book'][1]", "$['store']['book'][2]"]);
+  assert_eq!(paths("$.store.book[?@.price > $.store.bicycle.price || @.author == \"Nigel Rees\"]",
+                   BOOKSTORE),
+             vec!["$['store']['book'][0]"]);
+  assert_eq!(paths("$.store.book[?length(@.title) >= 20]", BOOKSTORE),
+             vec!["$['store']['book'][0]", "$['store']['book'][3]"]);
+  assert_eq!(paths("$.store[?count(@.*) == 2]", BOOKSTORE), vec!["$['store']['bicycle']"]);
+}
+
+#[test]
+fn slices() {
+  let input = r##"["a", "b", "c", "d", "e", "f", "g"]"##;
+
+  assert_eq!(paths("$[1:3]", input), vec!["$[1]", "$[2]"]);
+  assert_eq!(paths("$[5:]", input), vec!["$[5]", "$[6]"]);
+  assert_eq!(paths("$[1:5:2]", input), vec!["$[1]", "$[3]"]);
+  assert_eq!(paths("$[5:1:-2]", input), vec!["$[3]", "$[5]"]);
+  assert_eq!(paths("$[::0]", input), Vec::<String>::new());
+
+  let doc = parse_json(input).unwrap();
+  let reversed: Vec<String> = JsonPath::parse("$[::-1]").unwrap()
+                                                        .query(&doc)
+                                                        .into_iter()
+                                                        .map(|m| m.path)
+                                                        .collect();
+  assert_eq!(reversed, vec!["$[6]", "$[5]", "$[4]", "$[3]", "$[2]", "$[1]", "$[0]"]);
+}
+
+#[test]
+fn normalized_path_escapes() {
+  let input = r##"{"it's": {"a\\b": {"\n": 1}}}"##;
+
+  assert_eq!(paths("$['it\\'s'][\"a\\\\b\"]['\\n']", input), vec!["$['it\\'s']['a\\\\b']['\\n']"]);
+}
+
+#[test]
+fn syntax_errors() {
+  assert_eq!(JsonPath::parse("store").unwrap_err().position, 0);
+  assert_eq!(JsonPath::parse("$[01]").unwrap_err().position, 2);
+  assert_eq!(JsonPath::parse("$[?@.* == 1]").unwrap_err().message,
+             "only singular queries can be compared");
+  assert_eq!(JsonPath::parse("$[?1 == 1 &&]").unwrap_err().position, 12);
+  assert_eq!(JsonPath::parse("$[?foo(@)]").unwrap_err().message, "unknown function");
+  assert!(JsonPath::parse("$.a ").is_err());
+
+  let out_of_range = ["$[9007199254740992]", "$[-9007199254740992]", "$[-9223372036854775808]",
+                      "$[1:2:-9223372036854775808]"];
+
+  for &query in &out_of_range {
+    assert_eq!(JsonPath::parse(query).unwrap_err().message, "integer out of range", "{}", query);
+  }
+  assert!(JsonPath::parse("$[-9007199254740991]").is_ok());
+}
@@ -0,0 +1,46 @@
+extern crate json_parser;
+use json_parser::*;
+
+fn parse_with(input: &str, options: ParserOptions) -> Result<JsonValue, String> {
+  JsonParser::with_options(input, options).parse().map_err(|e| format!("{:?}", e))
+}
+
+fn assert_limit(input: &str, options: ParserOptions, kind: &str) {
+  match parse_with(input, options) {
+    Err(e) => assert!(e.contains(kind), "{} should contain {}", e, kind),
+    Ok(value) => panic!("{} should fail, got {:?}", input, value),
+  }
+}
+
+#[test]
+fn input_and_string_length() {
+  let options = ParserOptions { max_input_len: Some(10), ..ParserOptions::default() };
+  assert!(parse_with("[1, 2, 3]", options.clone()).is_ok());
+  assert_limit("[1, 2, 3, 4]", options, "InputTooLarge");
+
+  let options = ParserOptions { max_string_len: Some(5), ..ParserOptions::default() };
+  assert!(parse_with(r#"{"abcde": "ab\"d"}"#, options.clone()).is_ok());
+  assert_limit(r#"["abcdef"]"#, options.clone(), "StringTooLong");
+  assert_limit(r#"{"abcdef": 1}"#, options.clone(), "StringTooLong");
+  assert_limit(r#"["ab\"\"d"]"#, options, "StringTooLong");
+}
+
+#[test]
+fn container_length_and_nodes() {
+  let options = ParserOptions { max_container_len: Some(3), ..ParserOptions::default() };
+  assert!(parse_with(r#"[1, 2, {"a": 1, "b": 2, "c": [3]}]"#, options.clone()).is_ok());
+  assert_limit("[1, 2, 3, 4]", options.clone(), "TooManyElements");
+  assert_limit(r#"{"a": 1, "b": 2, "c": 3, "d": 4}"#, options, "TooManyElements");
+
+  let options = ParserOptions { max_nodes: Some(4), ..ParserOptions::default() };
+  assert!(parse_with(r#"{"a": [1, 2]}"#, options.clone()).is_ok());
+  assert_limit(r#"{"a": [1, 2], "b": null}"#, options, "TooManyNodes");
+}
+
+#[test]
+fn number_length() {
+  let options = ParserOptions { max_number_len: Some(6), ..ParserOptions::default() };
+  assert!(parse_with("[-1.234, 123456]", options.clone()).is_ok());
+  assert_limit("[1234567]", options.clone(), "NumberTooLong");
+  assert_limit("[-1.2345]", options, "NumberTooLong");
+}
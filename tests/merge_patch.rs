@@ -0,0 +1,56 @@
+extern crate json_parser;
+use json_parser::*;
+use json_parser::merge_patch::*;
+
+// Test cases from RFC 7386, appendix A: (original, patch, result).
+const RFC_EXAMPLES: &'static [(&'static str, &'static str, &'static str)] = &[
+  (r##"{"a":"b"}"##, r##"{"a":"c"}"##, r##"{"a":"c"}"##),
+  (r##"{"a":"b"}"##, r##"{"b":"c"}"##, r##"{"a":"b","b":"c"}"##),
+  (r##"{"a":"b"}"##, r##"{"a":null}"##, r##"{}"##),
+  (r##"{"a":"b","b":"c"}"##, r##"{"a":null}"##, r##"{"b":"c"}"##),
+  (r##"{"a":["b"]}"##, r##"{"a":"c"}"##, r##"{"a":"c"}"##),
+  (r##"{"a":"c"}"##, r##"{"a":["b"]}"##, r##"{"a":["b"]}"##),
+  (r##"{"a":{"b":"c"}}"##, r##"{"a":{"b":"d","c":null}}"##, r##"{"a":{"b":"d"}}"##),
+  (r##"{"a":[{"b":"c"}]}"##, r##"{"a":[1]}"##, r##"{"a":[1]}"##),
+  (r##"["a","b"]"##, r##"["c","d"]"##, r##"["c","d"]"##),
+  (r##"{"a":"b"}"##, r##"["c"]"##, r##"["c"]"##),
+  (r##"{"a":"foo"}"##, r##"null"##, r##"null"##),
+  (r##"{"a":"foo"}"##, r##""bar""##, r##""bar""##),
+  (r##"{"e":null}"##, r##"{"a":1}"##, r##"{"e":null,"a":1}"##),
+  (r##"[1,2]"##, r##"{"a":"b","c":null}"##, r##"{"a":"b"}"##),
+  (r##"{}"##, r##"{"a":{"bb":{"ccc":null}}}"##, r##"{"a":{"bb":{}}}"##),
+];
+
+#[test]
+fn rfc_examples() {
+  for &(original, patch, result) in RFC_EXAMPLES {
+    let mut target = parse_json(original).unwrap();
+    apply_merge_patch(&mut target, &parse_json(patch).unwrap());
+
+    assert_eq!(target, parse_json(result).unwrap(), "patching {} with {}", original, patch);
+  }
+}
+
+#[test]
+fn generated_patch_round_trips() {
+  for &(original, _, result) in RFC_EXAMPLES {
+    let source = parse_json(original).unwrap();
+    let target = parse_json(result).unwrap();
+    let patch = create_merge_patch(&source, &target);
+
+    let mut patched = source.clone();
+    apply_merge_patch(&mut patched, &patch);
+
+    assert_eq!(patched, target, "patching {} with the generated patch", original);
+  }
+}
+
+#[test]
+fn generated_patch_is_minimal() {
+  let source = parse_json(r##"{"title":"Goodbye!","author":{"givenName":"John","familyName":"Doe"},"tags":["example","sample"],"content":"This will be unchanged"}"##).unwrap();
+  let target = parse_json(r##"{"title":"Hello!","author":{"givenName":"John"},"tags":["example"],"content":"This will be unchanged","phoneNumber":"+01-123-456-7890"}"##).unwrap();
+  let expected = parse_json(r##"{"title":"Hello!","phoneNumber":"+01-123-456-7890","author":{"familyName":null},"tags":["example"]}"##).unwrap();
+
+  assert_eq!(create_merge_patch(&source, &target), expected);
+  assert_eq!(create_merge_patch(&source, &source), parse_json("{}").unwrap());
+}
@@ -0,0 +1,57 @@
+#![cfg(unix)]
+
+extern crate json_parser;
+use json_parser::*;
+use json_parser::mmap::*;
+use std::borrow::Cow;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+fn temp_file(name: &str, content: &[u8]) -> PathBuf {
+  let path = env::temp_dir().join(format!("json-parser-{}-{}.json", name, std::process::id()));
+  fs::write(&path, content).unwrap();
+  path
+}
+
+#[test]
+fn strings_borrow_from_the_mapping() {
+  let content = r##"{"name": "plain", "escaped": "tab\there", "list": [1, 2.5, null]}"##;
+  let path = temp_file("borrow", content.as_bytes());
+  let file = unsafe { MappedFile::open(&path).unwrap() };
+  fs::remove_file(&path).unwrap();
+
+  let value = file.parse().unwrap();
+  assert_eq!(value, parse_json(content).unwrap());
+
+  let mapping = file.as_bytes().as_ptr_range();
+
+  match *value.pointer("/name").unwrap() {
+    JsonValue::String(Cow::Borrowed(s)) => assert!(mapping.contains(&s.as_ptr())),
+    ref other => panic!("unexpected value {:?}", other),
+  }
+
+  match *value.pointer("/escaped").unwrap() {
+    JsonValue::String(Cow::Owned(ref s)) => assert_eq!(s, "tab\there"),
+    ref other => panic!("unexpected value {:?}", other),
+  }
+}
+
+#[test]
+fn empty_and_invalid_files() {
+  let path = temp_file("empty", b"");
+  let file = unsafe { MappedFile::open(&path).unwrap() };
+  fs::remove_file(&path).unwrap();
+  assert_eq!(file.as_bytes(), b"");
+  assert!(file.parse().is_err());
+
+  let path = temp_file("utf8", b"[\"\xff\"]");
+  let file = unsafe { MappedFile::open(&path).unwrap() };
+  fs::remove_file(&path).unwrap();
+  match file.parse() {
+    Err(MappedError::Utf8(_)) => {}
+    other => panic!("unexpected result {:?}", other),
+  }
+
+  assert!(unsafe { MappedFile::open(env::temp_dir().join("json-parser-missing.json")) }.is_err());
+}
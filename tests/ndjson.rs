@@ -0,0 +1,93 @@
+extern crate json_parser;
+use json_parser::*;
+use json_parser::ndjson::*;
+use std::panic;
+use std::thread;
+use std::time::Duration;
+
+fn options(threads: usize, chunk_size: usize) -> NdjsonOptions {
+  NdjsonOptions { threads: threads, chunk_size: chunk_size }
+}
+
+#[test]
+fn results_in_order() {
+  let input: String = (0..1000).map(|i| format!("{{\"id\": {}, \"tags\": [\"a\", \"b\"]}}\n", i)).collect();
+
+  for &(threads, chunk_size) in &[(1, 1 << 20), (4, 1), (4, 100), (0, 1000)] {
+    let results = parse_parallel(&input, &options(threads, chunk_size));
+    assert_eq!(results.len(), 1000);
+
+    for (i, result) in results.into_iter().enumerate() {
+      assert_eq!(result.unwrap().pointer("/id").ok(), Some(&JsonValue::Number(i as f64)));
+    }
+  }
+}
+
+#[test]
+fn error_line_numbers() {
+  let input = "{\"a\": 1}\n\n  [1, 2]\r\n{\"b\" 2}\n[3]\n[4,\n";
+
+  for &chunk_size in &[1, 5, 1000] {
+    let results = parse_parallel(input, &options(3, chunk_size));
+    let errors: Vec<usize> = results.iter().filter_map(|r| r.as_ref().err()).map(|e| e.line).collect();
+
+    assert_eq!(results.len(), 5);
+    assert_eq!(results[1].as_ref().unwrap(), &parse_json("[1, 2]").unwrap());
+    assert_eq!(errors, vec![4, 6]);
+  }
+}
+
+#[test]
+fn trailing_characters() {
+  let input = "{\"a\":1} {\"b\":2}\n{\"a\":1} garbage\n{\"a\":1} \r\n";
+  let results = parse_parallel(input, &options(2, 1000));
+  let errors: Vec<(usize, usize)> =
+    results.iter().filter_map(|r| r.as_ref().err()).map(|e| (e.line, e.error.offset())).collect();
+
+  assert_eq!(results.len(), 3);
+  assert_eq!(errors, vec![(1, 8), (2, 8)]);
+  assert_eq!(results[2].as_ref().unwrap(), &parse_json("{\"a\":1}").unwrap());
+}
+
+#[test]
+fn streaming_callback() {
+  let input = "1\n2\n3";
+  let mut seen = Vec::new();
+
+  for_each_parallel(input, &options(2, 1), |result| seen.push(result.unwrap()));
+
+  assert_eq!(seen, vec![JsonValue::Number(1.0), JsonValue::Number(2.0), JsonValue::Number(3.0)]);
+  assert!(parse_parallel("", &NdjsonOptions::default()).is_empty());
+}
+
+#[test]
+fn slow_consumer() {
+  let input: String = (0..200).map(|i| format!("{}\n", i)).collect();
+  let mut seen = 0;
+
+  for_each_parallel(&input, &options(4, 1), |result| {
+    assert_eq!(result.unwrap(), JsonValue::Number(seen as f64));
+    seen += 1;
+
+    if seen % 50 == 0 {
+      thread::sleep(Duration::from_millis(10));
+    }
+  });
+
+  assert_eq!(seen, 200);
+}
+
+#[test]
+fn panicking_callback() {
+  let input: String = (0..200).map(|i| format!("{}\n", i)).collect();
+
+  let result = panic::catch_unwind(|| {
+    for_each_parallel(&input, &options(4, 1), |result| {
+      if result.unwrap() == JsonValue::Number(10.0) {
+        panic!("stop");
+      }
+    });
+  });
+
+  assert!(result.is_err());
+}
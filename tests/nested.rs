@@ -99,3 +99,24 @@ fn invalid_bigger_object_nested_beautified_double_comma() {
 
   assert_eq!(parse_json(input).unwrap(), expected);
 }
+
+#[test]
+fn empty_containers() {
+  let input = r##"{"an_object": {}, "an_array": [], "nested": [{ }, [ ]], "last": []}"##;
+
+  let expected = {
+    let mut top_level_obj = HashMap::new();
+    top_level_obj.insert(Cow::Borrowed("an_object"), JsonValue::Object(HashMap::new()));
+    top_level_obj.insert(Cow::Borrowed("an_array"), JsonValue::Array(vec![]));
+    top_level_obj.insert(Cow::Borrowed("nested"),
+                         JsonValue::Array(vec![JsonValue::Object(HashMap::new()), JsonValue::Array(vec![])]));
+    top_level_obj.insert(Cow::Borrowed("last"), JsonValue::Array(vec![]));
+
+    top_level_obj
+  };
+  let expected = JsonValue::Object(expected);
+
+  assert_eq!(parse_json(input).unwrap(), expected);
+  assert_eq!(parse_json("[]").unwrap(), JsonValue::Array(vec![]));
+  assert_eq!(parse_json("{ }").unwrap(), JsonValue::Object(HashMap::new()));
+}
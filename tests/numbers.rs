@@ -0,0 +1,17 @@
+extern crate json_parser;
+use json_parser::*;
+
+#[test]
+fn number_at_end_of_input() {
+  assert_eq!(parse_json("1").unwrap(), JsonValue::Number(1.0));
+  assert_eq!(parse_json("-2.5").unwrap(), JsonValue::Number(-2.5));
+  assert!(parse_json("3.").is_err());
+}
+
+#[test]
+fn digit_after_decimal_point() {
+  assert_eq!(parse_json("[0.5]").unwrap(), JsonValue::Array(vec![JsonValue::Number(0.5)]));
+  assert!(parse_json("[3.]").is_err());
+  assert!(parse_json("[3.,1]").is_err());
+  assert!(parse_json("[-.5]").is_err());
+}
@@ -0,0 +1,78 @@
+extern crate json_parser;
+use json_parser::*;
+use json_parser::parallel::*;
+
+fn options(threads: usize) -> ParallelOptions {
+  ParallelOptions { threads: threads, min_size: 0, ..ParallelOptions::default() }
+}
+
+fn records(n: usize) -> String {
+  let records: Vec<String> = (0..n)
+    .map(|i| format!(r#"{{"id": {}, "text": "a, \"b]\" {{c}} \\", "nested": [[{}], {{"x": [1, 2]}}]}}"#, i, i))
+    .collect();
+  format!("[{}]", records.join(",\n "))
+}
+
+#[test]
+fn same_result_as_sequential() {
+  let input = records(500);
+  let expected = parse_json(&input).unwrap();
+
+  for &threads in &[1, 2, 3, 8] {
+    assert_eq!(parse_parallel(&input, &options(threads)).unwrap(), expected);
+  }
+
+  for input in &["[]", "[ ]", "[1]", "[1, 2,]", "[\"]\", \"\\\"\"]", "{\"a\": [1, 2]}", "\"a\"", "[[], {}]"] {
+    assert_eq!(parse_parallel(input, &options(4)).unwrap(), parse_json(input).unwrap(), "{}", input);
+  }
+}
+
+#[test]
+fn same_errors_as_sequential() {
+  for input in &["[1 2, 3]", "[1, , 2]", "[,]", "[1, {\"a\": ]}, 2]", "[\"abc", "[1, 2", "[{]}, 1]", "[\"abc\\"] {
+    assert_eq!(format!("{:?}", parse_parallel(input, &options(4)).unwrap_err()),
+               format!("{:?}", parse_json(input).unwrap_err()),
+               "{}",
+               input);
+  }
+}
+
+#[test]
+fn small_documents_are_parsed_sequentially() {
+  let input = records(10);
+  let options = ParallelOptions { threads: 4, ..ParallelOptions::default() };
+
+  assert_eq!(parse_parallel(&input, &options).unwrap(), parse_json(&input).unwrap());
+}
+
+#[test]
+fn parser_options_apply_to_the_whole_document() {
+  let input = records(100);
+  let nodes = 1 + 100 * 10;
+
+  let limits = vec![ParserOptions { max_nodes: Some(nodes), ..ParserOptions::default() },
+                    ParserOptions { max_nodes: Some(nodes - 1), ..ParserOptions::default() },
+                    ParserOptions { max_nodes: Some(200), ..ParserOptions::default() },
+                    ParserOptions { max_input_len: Some(input.len() - 1), ..ParserOptions::default() },
+                    ParserOptions { max_container_len: Some(99), ..ParserOptions::default() },
+                    ParserOptions { max_depth: 2, ..ParserOptions::default() },
+                    ParserOptions { max_depth: 0, ..ParserOptions::default() },
+                    ParserOptions { max_string_len: Some(10), ..ParserOptions::default() },
+                    ParserOptions { lazy_depth: Some(2), ..ParserOptions::default() },
+                    ParserOptions { lazy_depth: Some(0), ..ParserOptions::default() }];
+
+  let results: Vec<bool> = limits.iter()
+                                 .map(|parser| JsonParser::with_options(&input, parser.clone()).parse().is_ok())
+                                 .collect();
+  assert_eq!(results, vec![true, false, false, false, false, false, false, false, true, true]);
+
+  for parser in limits {
+    let expected = JsonParser::with_options(&input, parser.clone()).parse();
+    let options = ParallelOptions { parser: parser, ..options(4) };
+
+    match (parse_parallel(&input, &options), expected) {
+      (Ok(value), Ok(expected)) => assert_eq!(value, expected),
+      (result, expected) => assert_eq!(format!("{:?}", result), format!("{:?}", expected)),
+    }
+  }
+}
@@ -0,0 +1,74 @@
+extern crate json_parser;
+use json_parser::*;
+use json_parser::arena::{self, Bump};
+use json_parser::structural;
+use json_parser::tape::Tape;
+use std::error::Error;
+
+#[test]
+fn position() {
+  let error = parse_json("{\n  \"a\": 1,\n  \"b\": 2 x\n}").unwrap_err();
+
+  assert_eq!(error.line(), 3);
+  assert_eq!(error.column(), 10);
+  assert_eq!(error.offset(), 21);
+  assert_eq!(*error.kind(), ParseErrorKind::UnexpectedCharacter('x', vec![',', '}']));
+}
+
+#[test]
+fn display() {
+  let error = parse_json("[\n  1,\n  {\"a\" x").unwrap_err();
+  assert_eq!(error.to_string(), "expected ':' at /1, line 3, column 8, found 'x'");
+
+  let error = parse_json("[1, 2").unwrap_err();
+  assert_eq!(error.to_string(), "unexpected end of input at line 1, column 6");
+
+  let error = parse_json("[1 2]").unwrap_err();
+  assert_eq!(error.to_string(), "expected ',' or ']' at line 1, column 4, found '2'");
+
+  let error = parse_json("?").unwrap_err();
+  assert_eq!(error.to_string(),
+             "expected '\"', '{', '[', '-', 't', 'f' or 'n' at line 1, column 1, found '?'");
+}
+
+#[test]
+fn error_trait() {
+  let options = ParserOptions { max_depth: 1, ..ParserOptions::default() };
+  let error: Box<dyn Error> = Box::new(JsonParser::with_options("[[]]", options).parse().unwrap_err());
+
+  assert_eq!(error.to_string(), "nesting too deep at /0, line 1, column 2");
+  assert!(error.downcast_ref::<ParseError>().is_some());
+}
+
+#[test]
+fn pointer() {
+  let input = r#"{"friends": [{"name": "a"}, {"name": "b"}, {"name": tru}]}"#;
+  let error = parse_json(input).unwrap_err();
+  assert_eq!(error.pointer(), Some("/friends/2/name"));
+  assert_eq!(error.to_string(), "expected 'e' at /friends/2/name, line 1, column 56, found '}'");
+
+  // Errors between items point to their container.
+  assert_eq!(parse_json(r#"{"a/b": [{"~c": 1 2}]}"#).unwrap_err().pointer(), Some("/a~1b/0"));
+  assert_eq!(parse_json(r#"{"a": [1, 2], 3}"#).unwrap_err().pointer(), Some(""));
+  assert_eq!(parse_json("[1, [2, [").unwrap_err().pointer(), Some("/1/1/0"));
+
+  // Errors inside lazy values point to the raw value.
+  let options = ParserOptions { lazy_depth: Some(1), ..ParserOptions::default() };
+  let error = JsonParser::with_options(r#"{"a": [0, {"b": tru}]}"#, options).parse().unwrap_err();
+  assert_eq!(error.pointer(), Some("/a"));
+}
+
+#[test]
+fn pointer_unknown() {
+  let input = r#"{"a": [0, {"b": tru}]}"#;
+  let arena = Bump::new();
+
+  let errors = vec![structural::parse(input).unwrap_err(),
+                    Tape::parse(input).err().unwrap(),
+                    arena::parse_in(input, &arena).unwrap_err()];
+
+  for error in errors {
+    assert_eq!(error.pointer(), None);
+    assert_eq!(error.to_string(), "expected 'e' at line 1, column 20, found '}'");
+  }
+}
@@ -0,0 +1,161 @@
+extern crate json_parser;
+use json_parser::*;
+use json_parser::patch::*;
+
+fn apply<'a>(doc: &'a str, patch: &'a str) -> (JsonValue<'a>, PatchResult<()>) {
+  let mut doc = parse_json(doc).unwrap();
+  let operations = parse_patch(&parse_json(patch).unwrap()).unwrap();
+  let result = apply_patch(&mut doc, &operations);
+
+  (doc, result)
+}
+
+fn assert_patch(doc: &str, patch: &str, expected: &str) {
+  let (doc, result) = apply(doc, patch);
+
+  assert_eq!(result, Ok(()));
+  assert_eq!(doc, parse_json(expected).unwrap());
+}
+
+// Examples from RFC 6902, appendix A.
+
+#[test]
+fn rfc_add() {
+  // A.1. Adding an Object Member
+  assert_patch(r##"{ "foo": "bar" }"##,
+               r##"[ { "op": "add", "path": "/baz", "value": "qux" } ]"##,
+               r##"{ "baz": "qux", "foo": "bar" }"##);
+
+  // A.2. Adding an Array Element
+  assert_patch(r##"{ "foo": [ "bar", "baz" ] }"##,
+               r##"[ { "op": "add", "path": "/foo/1", "value": "qux" } ]"##,
+               r##"{ "foo": [ "bar", "qux", "baz" ] }"##);
+
+  // A.10. Adding a Nested Member Object
+  assert_patch(r##"{ "foo": "bar" }"##,
+               r##"[ { "op": "add", "path": "/child", "value": { "grandchild": { } } } ]"##,
+               r##"{ "foo": "bar", "child": { "grandchild": {} } }"##);
+
+  // A.11. Ignoring Unrecognized Elements
+  assert_patch(r##"{ "foo": "bar" }"##,
+               r##"[ { "op": "add", "path": "/baz", "value": "qux", "xyz": 123 } ]"##,
+               r##"{ "foo": "bar", "baz": "qux" }"##);
+
+  // A.16. Adding an Array Value
+  assert_patch(r##"{ "foo": ["bar"] }"##,
+               r##"[ { "op": "add", "path": "/foo/-", "value": ["abc", "def"] } ]"##,
+               r##"{ "foo": ["bar", ["abc", "def"]] }"##);
+}
+
+#[test]
+fn rfc_remove_replace() {
+  // A.3. Removing an Object Member
+  assert_patch(r##"{ "baz": "qux", "foo": "bar" }"##,
+               r##"[ { "op": "remove", "path": "/baz" } ]"##,
+               r##"{ "foo": "bar" }"##);
+
+  // A.4. Removing an Array Element
+  assert_patch(r##"{ "foo": [ "bar", "qux", "baz" ] }"##,
+               r##"[ { "op": "remove", "path": "/foo/1" } ]"##,
+               r##"{ "foo": [ "bar", "baz" ] }"##);
+
+  // A.5. Replacing a Value
+  assert_patch(r##"{ "baz": "qux", "foo": "bar" }"##,
+               r##"[ { "op": "replace", "path": "/baz", "value": "boo" } ]"##,
+               r##"{ "baz": "boo", "foo": "bar" }"##);
+}
+
+#[test]
+fn rfc_move_copy() {
+  // A.6. Moving a Value
+  assert_patch(r##"{ "foo": { "bar": "baz", "waldo": "fred" }, "qux": { "corge": "grault" } }"##,
+               r##"[ { "op": "move", "from": "/foo/waldo", "path": "/qux/thud" } ]"##,
+               r##"{ "foo": { "bar": "baz" }, "qux": { "corge": "grault", "thud": "fred" } }"##);
+
+  // A.7. Moving an Array Element
+  assert_patch(r##"{ "foo": [ "all", "grass", "cows", "eat" ] }"##,
+               r##"[ { "op": "move", "from": "/foo/1", "path": "/foo/3" } ]"##,
+               r##"{ "foo": [ "all", "cows", "eat", "grass" ] }"##);
+
+  assert_patch(r##"{ "foo": [1, 2] }"##,
+               r##"[ { "op": "copy", "from": "/foo", "path": "/bar" } ]"##,
+               r##"{ "foo": [1, 2], "bar": [1, 2] }"##);
+
+  let (_, result) = apply(r##"{ "foo": { "bar": 1 } }"##,
+                          r##"[ { "op": "move", "from": "/foo", "path": "/foo/bar/baz" } ]"##);
+  assert_eq!(result, Err(PatchError::new(0, PatchErrorKind::MoveIntoChild)));
+}
+
+#[test]
+fn rfc_test() {
+  // A.8. Testing a Value: Success
+  assert_patch(r##"{ "baz": "qux", "foo": [ "a", 2, "c" ] }"##,
+               r##"[
+                 { "op": "test", "path": "/baz", "value": "qux" },
+                 { "op": "test", "path": "/foo/1", "value": 2 }
+               ]"##,
+               r##"{ "baz": "qux", "foo": [ "a", 2, "c" ] }"##);
+
+  // A.9. Testing a Value: Error
+  let (_, result) = apply(r##"{ "baz": "qux" }"##,
+                          r##"[ { "op": "test", "path": "/baz", "value": "bar" } ]"##);
+  assert_eq!(result, Err(PatchError::new(0, PatchErrorKind::TestFailed)));
+
+  // A.14. ~ Escape Ordering
+  assert_patch(r##"{ "/": 9, "~1": 10 }"##,
+               r##"[ {"op": "test", "path": "/~01", "value": 10} ]"##,
+               r##"{ "/": 9, "~1": 10 }"##);
+
+  // A.15. Comparing Strings and Numbers
+  let (_, result) = apply(r##"{ "/": 9, "~1": 10 }"##,
+                          r##"[ {"op": "test", "path": "/~01", "value": "10"} ]"##);
+  assert_eq!(result, Err(PatchError::new(0, PatchErrorKind::TestFailed)));
+}
+
+#[test]
+fn rfc_errors() {
+  // A.12. Adding to a Nonexistent Target
+  let (_, result) = apply(r##"{ "foo": "bar" }"##,
+                          r##"[ { "op": "add", "path": "/baz/bat", "value": "qux" } ]"##);
+  assert_eq!(result,
+             Err(PatchError::new(0, PatchErrorKind::Pointer(PointerError::MissingKey("baz".to_string())))));
+
+  let invalid = parse_json(r##"[ { "op": "frobnicate", "path": "/foo" } ]"##).unwrap();
+  assert_eq!(parse_patch(&invalid),
+             Err(PatchError::new(0, PatchErrorKind::InvalidOperation("unknown `op`"))));
+}
+
+#[test]
+fn failed_patch_is_rolled_back() {
+  let original = r##"{ "foo": [ "bar", "baz" ], "qux": { "a": 1 } }"##;
+
+  let (doc, result) = apply(original,
+                            r##"[
+                              { "op": "add", "path": "/foo/-", "value": "appended" },
+                              { "op": "remove", "path": "/foo/0" },
+                              { "op": "replace", "path": "/qux/a", "value": 2 },
+                              { "op": "move", "from": "/qux", "path": "/moved" },
+                              { "op": "copy", "from": "/moved", "path": "/qux" },
+                              { "op": "add", "path": "/qux", "value": null },
+                              { "op": "test", "path": "/moved/a", "value": 3 }
+                            ]"##);
+
+  assert_eq!(result, Err(PatchError::new(6, PatchErrorKind::TestFailed)));
+  assert_eq!(doc, parse_json(original).unwrap());
+}
+
+#[test]
+fn dash_member_of_object() {
+  assert_patch(r#"{"a": {}}"#, r#"[{"op": "add", "path": "/a/-", "value": 1}]"#, r#"{"a": {"-": 1}}"#);
+
+  let original = r#"{"obj": {"b": 2}, "arr": [1]}"#;
+  let (doc, result) = apply(original,
+                            r#"[
+                              { "op": "add", "path": "/obj/-", "value": 1 },
+                              { "op": "add", "path": "/arr/-", "value": 2 },
+                              { "op": "test", "path": "/obj/-", "value": 2 }
+                            ]"#);
+
+  assert_eq!(result, Err(PatchError::new(2, PatchErrorKind::TestFailed)));
+  assert_eq!(doc, parse_json(original).unwrap());
+}
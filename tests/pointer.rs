@@ -0,0 +1,76 @@
+extern crate json_parser;
+use json_parser::*;
+use std::borrow::Cow;
+
+// The example document from RFC 6901, section 5.
+const RFC_DOCUMENT: &'static str = r##"{
+  "foo": ["bar", "baz"],
+  "": 0,
+  "a/b": 1,
+  "c%d": 2,
+  "e^f": 3,
+  "g|h": 4,
+  "i\\j": 5,
+  "k\"l": 6,
+  " ": 7,
+  "m~n": 8
+}"##;
+
+#[test]
+fn rfc_examples() {
+  let doc = parse_json(RFC_DOCUMENT).unwrap();
+
+  assert_eq!(doc.pointer("").unwrap(), &doc);
+  assert_eq!(doc.pointer("/foo").unwrap(),
+             &JsonValue::Array(vec![JsonValue::String(Cow::Borrowed("bar")),
+                                    JsonValue::String(Cow::Borrowed("baz"))]));
+  assert_eq!(doc.pointer("/foo/0").unwrap(), &JsonValue::String(Cow::Borrowed("bar")));
+  assert_eq!(doc.pointer("/").unwrap(), &JsonValue::Number(0.0));
+  assert_eq!(doc.pointer("/a~1b").unwrap(), &JsonValue::Number(1.0));
+  assert_eq!(doc.pointer("/c%d").unwrap(), &JsonValue::Number(2.0));
+  assert_eq!(doc.pointer("/e^f").unwrap(), &JsonValue::Number(3.0));
+  assert_eq!(doc.pointer("/g|h").unwrap(), &JsonValue::Number(4.0));
+  assert_eq!(doc.pointer("/i\\j").unwrap(), &JsonValue::Number(5.0));
+  assert_eq!(doc.pointer("/k\"l").unwrap(), &JsonValue::Number(6.0));
+  assert_eq!(doc.pointer("/ ").unwrap(), &JsonValue::Number(7.0));
+  assert_eq!(doc.pointer("/m~0n").unwrap(), &JsonValue::Number(8.0));
+}
+
+#[test]
+fn lookup_errors() {
+  let doc = parse_json(RFC_DOCUMENT).unwrap();
+
+  assert_eq!(doc.pointer("foo"), Err(PointerError::InvalidSyntax));
+  assert_eq!(doc.pointer("/m~2n"), Err(PointerError::InvalidSyntax));
+  assert_eq!(doc.pointer("/bar"), Err(PointerError::MissingKey("bar".to_string())));
+  assert_eq!(doc.pointer("/foo/2"), Err(PointerError::IndexOutOfRange(2, 2)));
+  assert_eq!(doc.pointer("/foo/-"), Err(PointerError::IndexOutOfRange(2, 2)));
+  assert_eq!(doc.pointer("/foo/01"), Err(PointerError::InvalidIndex("01".to_string())));
+  assert_eq!(doc.pointer("/foo/0/x"), Err(PointerError::TypeMismatch("x".to_string())));
+}
+
+#[test]
+fn mutation() {
+  let mut doc = parse_json(r##"{"friends": [{"name": "Alice"}, {"name": "Bob"}]}"##).unwrap();
+
+  *doc.pointer_mut("/friends/0/name").unwrap() = JsonValue::String(Cow::Borrowed("Carol"));
+  assert_eq!(doc.pointer("/friends/0/name").unwrap(), &JsonValue::String(Cow::Borrowed("Carol")));
+
+  doc.pointer_insert("/friends/1", JsonValue::Null).unwrap();
+  doc.pointer_insert("/friends/-", JsonValue::Boolean(true)).unwrap();
+  assert_eq!(doc.pointer("/friends/1").unwrap(), &JsonValue::Null);
+  assert_eq!(doc.pointer("/friends/3").unwrap(), &JsonValue::Boolean(true));
+  assert_eq!(doc.pointer_insert("/friends/5", JsonValue::Null),
+             Err(PointerError::IndexOutOfRange(5, 4)));
+
+  let old = doc.pointer_replace("/friends/2/name", JsonValue::Number(1.0)).unwrap();
+  assert_eq!(old, JsonValue::String(Cow::Borrowed("Bob")));
+  assert_eq!(doc.pointer_replace("/friends/2/age", JsonValue::Null),
+             Err(PointerError::MissingKey("age".to_string())));
+
+  let removed = doc.pointer_remove("/friends/1").unwrap();
+  assert_eq!(removed, JsonValue::Null);
+  assert_eq!(doc.pointer_remove("/friends/0/name").unwrap(),
+             JsonValue::String(Cow::Borrowed("Carol")));
+  assert_eq!(doc.pointer_remove(""), Err(PointerError::RootRemoval));
+}
@@ -0,0 +1,126 @@
+extern crate json_parser;
+use json_parser::*;
+use json_parser::diff::*;
+use json_parser::json_path::JsonPath;
+use json_parser::merge_patch::apply_merge_patch;
+use json_parser::schema::Schema;
+use std::borrow::Cow;
+
+const MESSAGE: &'static str = r##"{
+  "route": "billing",
+  "payload": {"invoice": {"id": 17, "lines": [1, 2, 3]}, "note": "a \"quoted\" note"},
+  "attachments": [{"name": "a"}, [], {}]
+}"##;
+
+#[test]
+fn nested_containers_are_kept_raw() {
+  let options = ParserOptions { lazy_depth: Some(1), ..ParserOptions::default() };
+  let message = JsonParser::with_options(MESSAGE, options).parse().unwrap();
+
+  assert_eq!(message.pointer("/route").unwrap(), &JsonValue::String(Cow::Borrowed("billing")));
+
+  let payload = match *message.pointer("/payload").unwrap() {
+    JsonValue::Raw(ref raw) => raw,
+    ref other => panic!("expected a raw value, found {:?}", other),
+  };
+  assert_eq!(payload.get(),
+             r##"{"invoice": {"id": 17, "lines": [1, 2, 3]}, "note": "a \"quoted\" note"}"##);
+  assert_eq!(payload.parse().unwrap(),
+             parse_json(r##"{"invoice": {"id": 17, "lines": [1, 2, 3]}, "note": "a \"quoted\" note"}"##).unwrap());
+
+  match *message.pointer("/attachments").unwrap() {
+    JsonValue::Raw(ref raw) => assert_eq!(raw.get(), r##"[{"name": "a"}, [], {}]"##),
+    ref other => panic!("expected a raw value, found {:?}", other),
+  }
+}
+
+#[test]
+fn raw_values_can_be_parsed_level_by_level() {
+  let options = ParserOptions { lazy_depth: Some(1), ..ParserOptions::default() };
+  let message = JsonParser::with_options(MESSAGE, options.clone()).parse().unwrap();
+
+  let payload = match *message.pointer("/payload").unwrap() {
+    JsonValue::Raw(ref raw) => raw.parse_with_options(options).unwrap(),
+    _ => unreachable!(),
+  };
+
+  match *payload.pointer("/invoice").unwrap() {
+    JsonValue::Raw(ref raw) => assert_eq!(raw.get(), r##"{"id": 17, "lines": [1, 2, 3]}"##),
+    ref other => panic!("expected a raw value, found {:?}", other),
+  }
+}
+
+#[test]
+fn raw_values_are_validated() {
+  let options = ParserOptions { lazy_depth: Some(1), ..ParserOptions::default() };
+
+  assert!(JsonParser::with_options(r##"{"a": {"b" 1}}"##, options.clone()).parse().is_err());
+  assert!(JsonParser::with_options(r##"{"a": [1 2]}"##, options.clone()).parse().is_err());
+  assert!(JsonParser::with_options(r##"{"a": ["\x"]}"##, options.clone()).parse().is_err());
+  assert!(JsonParser::with_options(r##"{"a": [1, "##, options).parse().is_err());
+}
+
+fn lazy(json: &str) -> JsonValue {
+  let options = ParserOptions { lazy_depth: Some(1), ..ParserOptions::default() };
+  JsonParser::with_options(json, options).parse().unwrap()
+}
+
+#[test]
+fn lookups_go_through_raw_values() {
+  let message = lazy(MESSAGE);
+  let eager = parse_json(MESSAGE).unwrap();
+
+  assert_eq!(message, eager);
+  assert_eq!(message.pointer("/payload/invoice/lines/2").unwrap(), &JsonValue::Number(3.0));
+  assert_eq!(message.pointer("/attachments/0/name"), eager.pointer("/attachments/0/name"));
+  assert_eq!(message.pointer("/payload/missing"),
+             Err(PointerError::MissingKey("missing".to_string())));
+
+  let query = JsonPath::parse("$..lines[?@ > 1]").unwrap();
+  let values: Vec<&JsonValue> = query.query(&message).into_iter().map(|m| m.value).collect();
+  assert_eq!(values, vec![&JsonValue::Number(2.0), &JsonValue::Number(3.0)]);
+}
+
+#[test]
+fn raw_values_are_compared_by_content() {
+  assert_eq!(lazy("[[1, 2]]"), lazy("[[1,2]]"));
+  assert!(lazy("[[1, 2]]") != lazy("[[2, 1]]"));
+  assert!(diff(&lazy(r#"[{"a": 1}]"#), &lazy(r#"[{ "a" : 1 }]"#)).is_empty());
+
+  let old = lazy(r#"{"a": {"b": 1, "c": 2}}"#);
+  let new = parse_json(r#"{"a": {"b": 1, "c": 3}}"#).unwrap();
+  let paths: Vec<String> = diff(&old, &new)
+                             .into_iter()
+                             .map(|change| match change {
+                               Change::Changed { path, .. } => path,
+                               other => panic!("unexpected change {:?}", other),
+                             })
+                             .collect();
+  assert_eq!(paths, vec!["/a/c"]);
+}
+
+#[test]
+fn raw_values_are_parsed_to_be_modified() {
+  let mut message = lazy(MESSAGE);
+  *message.pointer_mut("/payload/invoice/id").unwrap() = JsonValue::Number(18.0);
+  message.pointer_remove("/attachments/1").unwrap();
+
+  let mut expected = parse_json(MESSAGE).unwrap();
+  *expected.pointer_mut("/payload/invoice/id").unwrap() = JsonValue::Number(18.0);
+  expected.pointer_remove("/attachments/1").unwrap();
+  assert_eq!(message, expected);
+
+  let mut target = lazy(r#"{"a": {"b": 1, "c": 2}}"#);
+  apply_merge_patch(&mut target, &lazy(r#"{"a": {"c": null}}"#));
+  assert_eq!(target, parse_json(r#"{"a": {"b": 1}}"#).unwrap());
+}
+
+#[test]
+fn raw_values_are_validated_by_schemas() {
+  let schema = Schema::compile(&lazy(r#"{"properties": {"a": {"type": "array", "items": {"type": "integer"}}}}"#))
+                 .unwrap();
+
+  assert!(schema.is_valid(&lazy(r#"{"a": [1, 2]}"#)));
+  assert!(!schema.is_valid(&lazy(r#"{"a": [1, "2"]}"#)));
+  assert!(!schema.is_valid(&lazy(r#"{"a": {}}"#)));
+}
@@ -0,0 +1,104 @@
+extern crate json_parser;
+use json_parser::*;
+
+fn recover(input: &str) -> (JsonValue, Vec<String>) {
+  let (value, errors) = JsonParser::new(input).parse_recovering();
+  (value, errors.iter().map(|e| e.to_string()).collect())
+}
+
+#[test]
+fn multiple_errors() {
+  let (value, errors) = recover(r#"[1, x, 3, {"a": tru, "b": 2, c: 0}, [4 5], "a\qb", 6,]"#);
+
+  assert_eq!(value, parse_json(r#"[1, null, 3, {"a": null, "b": 2}, [4], null, 6]"#).map(|mut value| {
+    if let JsonValue::Array(ref mut elements) = value {
+      elements[1] = JsonValue::Invalid;
+      elements[5] = JsonValue::Invalid;
+      if let JsonValue::Object(ref mut members) = elements[3] {
+        members.insert("a".into(), JsonValue::Invalid);
+      }
+    }
+    value
+  }).unwrap());
+
+  assert_eq!(errors,
+             vec!["expected '\"', '{', '[', '-', 't', 'f' or 'n' at /1, line 1, column 5, found 'x'",
+                  "expected 'e' at /3/a, line 1, column 20, found ','",
+                  "expected '\"' at /3, line 1, column 30, found 'c'",
+                  "expected ',' or ']' at /4, line 1, column 40, found '5'",
+                  "expected '\"', '\\\\', 'n', 'r' or 't' at /5, line 1, column 47, found 'q'"]);
+}
+
+#[test]
+fn unbalanced_brackets() {
+  let (value, errors) = recover(r#"[{"a": [1, 2}, {"b": [3}]"#);
+  assert_eq!(value, parse_json(r#"[{"a": [1, 2]}, {"b": [3]}]"#).unwrap());
+  assert_eq!(errors.len(), 2);
+
+  let (value, errors) = recover("[1, 2}, 3]");
+  assert_eq!(value, parse_json("[1, 2, 3]").unwrap());
+  assert_eq!(errors, vec!["expected ',' or ']' at line 1, column 6, found '}'"]);
+
+  let (value, errors) = recover(r#"{"a": [1, {"b": "#);
+  assert_eq!(format!("{:?}", value), r#"Object({"a": Array([Number(1.0), Object({"b": Invalid})])})"#);
+  assert_eq!(errors, vec!["unexpected end of input at /a/1/b, line 1, column 17"]);
+
+  let (value, errors) = recover("[\"abc\n, 1]");
+  assert_eq!(value, JsonValue::Array(vec![JsonValue::Invalid, JsonValue::Number(1.0)]));
+  assert_eq!(errors.len(), 1);
+}
+
+#[test]
+fn top_level_and_valid_input() {
+  assert_eq!(recover("x"), (JsonValue::Invalid, vec!["expected '\"', '{', '[', '-', 't', 'f' or 'n' at line 1, column 1, found 'x'".to_string()]));
+  assert_eq!(recover(r#"{"a": [1, 2]}"#), (parse_json(r#"{"a": [1, 2]}"#).unwrap(), vec![]));
+
+  let options = ParserOptions { max_depth: 2, max_container_len: Some(2), ..ParserOptions::default() };
+  let parser = JsonParser::with_options("[[[1]], [2], 3, 4]", options);
+  let (value, errors) = parser.parse_recovering();
+
+  assert_eq!(format!("{:?}", value), "Array([Array([Invalid]), Array([Number(2.0)])])");
+  assert_eq!(errors.len(), 3);
+}
+
+#[test]
+fn lazy_containers() {
+  let options = ParserOptions { lazy_depth: Some(1), ..ParserOptions::default() };
+
+  for &input in &["[[1, x], 2, [3]]", r#"[{"a": "]", "b": [x]}, 2, [3]]"#] {
+    let (value, errors) = JsonParser::with_options(input, options.clone()).parse_recovering();
+    assert_eq!(errors.len(), 1, "{}", input);
+
+    match value {
+      JsonValue::Array(ref elements) => {
+        assert_eq!(elements.len(), 3);
+        assert_eq!(elements[0], recover(input).0.pointer("/0").unwrap().clone());
+        assert_eq!(elements[1], JsonValue::Number(2.0));
+
+        match elements[2] {
+          JsonValue::Raw(ref raw) => assert_eq!(raw.get(), "[3]"),
+          ref other => panic!("expected a raw value, got {:?}", other),
+        }
+      }
+      ref other => panic!("expected an array, got {:?}", other),
+    }
+  }
+}
+
+#[test]
+fn lazy_containers_report_the_same_errors() {
+  let options = ParserOptions { lazy_depth: Some(1), ..ParserOptions::default() };
+
+  for &input in &[r#"{"a":[1,{"b":]},2]"#, r#"[[1,{"b":]},2]"#, r#"{"a": [[1], {"b": [2 3]}, x], "c": {"d": }}"#, "[[1, [2, x]], [3"] {
+    let (eager, eager_errors) = recover(input);
+    let (lazy, lazy_errors) = JsonParser::with_options(input, options.clone()).parse_recovering();
+
+    assert_eq!(lazy, eager, "{}", input);
+    assert_eq!(lazy_errors.iter().map(|e| e.to_string()).collect::<Vec<_>>(), eager_errors, "{}", input);
+  }
+
+  let (_, errors) = recover(r#"{"a":[1,{"b":]},2]"#);
+  assert_eq!(errors,
+             vec!["expected '\"', '{', '[', '-', 't', 'f' or 'n' at /a/1/b, line 1, column 14, found ']'",
+                  "unexpected character at line 1, column 16, found ','"]);
+}
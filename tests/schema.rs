@@ -0,0 +1,147 @@
+extern crate json_parser;
+use json_parser::*;
+use json_parser::schema::*;
+
+const PERSON_SCHEMA: &'static str = r##"{
+  "type": "object",
+  "properties": {
+    "name": { "type": "string", "minLength": 1, "pattern": "^[A-Z]" },
+    "age": { "$ref": "#/$defs/nonNegativeInteger" },
+    "email": { "type": "string", "pattern": "@" },
+    "tags": {
+      "type": "array",
+      "items": { "enum": ["admin", "user", "guest"] },
+      "uniqueItems": true,
+      "maxItems": 3
+    },
+    "position": {
+      "type": "array",
+      "prefixItems": [{ "type": "number" }, { "type": "number" }],
+      "items": false
+    },
+    "friends": { "type": "array", "items": { "$ref": "#" } }
+  },
+  "required": ["name", "age"],
+  "additionalProperties": false,
+  "$defs": {
+    "nonNegativeInteger": { "type": "integer", "minimum": 0 }
+  }
+}"##;
+
+fn errors(schema: &Schema, instance: &str) -> Vec<(String, String)> {
+  match schema.validate(&parse_json(instance).unwrap()) {
+    Ok(()) => Vec::new(),
+    Err(errors) => errors.into_iter().map(|e| (e.instance_path, e.schema_path)).collect(),
+  }
+}
+
+fn pair(instance_path: &str, schema_path: &str) -> (String, String) {
+  (instance_path.to_string(), schema_path.to_string())
+}
+
+#[test]
+fn valid_instance() {
+  let schema = Schema::compile(&parse_json(PERSON_SCHEMA).unwrap()).unwrap();
+
+  assert!(schema.is_valid(&parse_json(r##"{
+    "name": "Alice",
+    "age": 30,
+    "tags": ["admin", "user"],
+    "position": [1.5, -2],
+    "friends": [{ "name": "Bob", "age": 31, "friends": [] }]
+  }"##).unwrap()));
+}
+
+#[test]
+fn every_failure_is_reported() {
+  let schema = Schema::compile(&parse_json(PERSON_SCHEMA).unwrap()).unwrap();
+
+  let mut found = errors(&schema, r##"{
+    "name": "alice",
+    "age": 30.5,
+    "tags": ["admin", "root", "admin", "user"],
+    "position": [1, 2, 3],
+    "friends": [{ "name": "Bob" }],
+    "extra": true
+  }"##);
+  found.sort();
+
+  assert_eq!(found,
+             vec![pair("/age", "/$defs/nonNegativeInteger/type"),
+                  pair("/extra", "/additionalProperties"),
+                  pair("/friends/0", "/required"),
+                  pair("/name", "/properties/name/pattern"),
+                  pair("/position/2", "/properties/position/items"),
+                  pair("/tags", "/properties/tags/maxItems"),
+                  pair("/tags", "/properties/tags/uniqueItems"),
+                  pair("/tags/1", "/properties/tags/items/enum")]);
+}
+
+#[test]
+fn combinators() {
+  let schema = Schema::compile(&parse_json(r##"{
+    "allOf": [{ "type": ["number", "string"] }, { "not": { "const": 13 } }],
+    "anyOf": [{ "type": "string" }, { "multipleOf": 0.5 }],
+    "oneOf": [
+      { "type": "string" },
+      { "type": "number", "maximum": 10 },
+      { "type": "number", "minimum": 5 }
+    ]
+  }"##).unwrap()).unwrap();
+
+  assert!(schema.is_valid(&parse_json("3.5").unwrap()));
+  assert!(schema.is_valid(&parse_json("\"a string\"").unwrap()));
+  assert_eq!(errors(&schema, "13"), vec![pair("", "/allOf/1/not")]);
+  assert_eq!(errors(&schema, "3.3"), vec![pair("", "/anyOf")]);
+  assert_eq!(errors(&schema, "7"), vec![pair("", "/oneOf")]);
+  assert_eq!(errors(&schema, "null"), vec![pair("", "/oneOf"), pair("", "/allOf/0/type")]);
+}
+
+#[test]
+fn type_error_message() {
+  let schema = Schema::compile(&parse_json(r##"{ "type": ["string", "null"] }"##).unwrap()).unwrap();
+  let errors = schema.validate(&parse_json("1").unwrap()).unwrap_err();
+  assert_eq!(errors[0].message, "expected one of \"string\", \"null\"");
+}
+
+#[test]
+fn compile_errors() {
+  let compile = |schema: &str| Schema::compile(&parse_json(schema).unwrap()).unwrap_err();
+
+  assert_eq!(compile(r##"{ "$ref": "#/$defs/missing" }"##),
+             SchemaError { schema_path: "/$ref".to_string(), message: "unresolvable reference" });
+  assert_eq!(compile(r##"{ "items": { "type": "strin" } }"##),
+             SchemaError { schema_path: "/items/type".to_string(), message: "unknown type" });
+  assert_eq!(compile(r##"{ "pattern": "(" }"##),
+             SchemaError { schema_path: "/pattern".to_string(), message: "invalid regex" });
+  assert_eq!(compile("17").message, "schema must be an object or a boolean");
+}
+
+#[test]
+fn reference_cycles() {
+  let compile = |schema: &str| Schema::compile(&parse_json(schema).unwrap());
+
+  assert_eq!(compile(r##"{ "$ref": "#" }"##).unwrap_err(),
+             SchemaError { schema_path: "/$ref".to_string(), message: "reference cycle" });
+  assert_eq!(compile(r##"{ "$defs": { "a": { "$ref": "#" } }, "allOf": [{ "$ref": "#/$defs/a" }] }"##)
+               .unwrap_err()
+               .message,
+             "reference cycle");
+
+  // Recursion through a child of the instance terminates.
+  let schema = compile(r##"{ "type": "object", "properties": { "next": { "$ref": "#" } } }"##).unwrap();
+  assert!(schema.is_valid(&parse_json(r##"{ "next": { "next": {} } }"##).unwrap()));
+  assert!(!schema.is_valid(&parse_json(r##"{ "next": { "next": 1 } }"##).unwrap()));
+}
+
+#[test]
+fn deep_raw_const() {
+  let nested = format!("{}{}", "[".repeat(200), "]".repeat(200));
+  let json = format!(r#"{{ "const": {} }}"#, nested);
+  let options = ParserOptions { lazy_depth: Some(1), max_depth: 1000, ..ParserOptions::default() };
+  let schema = Schema::compile(&JsonParser::with_options(&json, options).parse().unwrap()).unwrap();
+
+  let options = ParserOptions { max_depth: 1000, ..ParserOptions::default() };
+  assert!(schema.is_valid(&JsonParser::with_options(&nested, options).parse().unwrap()));
+  assert!(!schema.is_valid(&parse_json("[]").unwrap()));
+}
@@ -0,0 +1,71 @@
+extern crate json_parser;
+use json_parser::*;
+use json_parser::structural::*;
+
+const DOC: &'static str = r##"{
+  "name": "Short \"Huffman\"",
+  "path": "C:\\Users\\",
+  "tags": ["a", "b,c", "[d]"],
+  "age": -21.5,
+  "active": true,
+  "spouse": null,
+  "friends": [{"id": 0}, {"id": 1}, {}],
+  "empty": []
+}"##;
+
+fn implementations() -> Vec<Implementation> {
+  vec![Implementation::Scalar, Implementation::Sse2, Implementation::Avx2]
+    .into_iter()
+    .filter(|implementation| implementation.is_supported())
+    .collect()
+}
+
+#[test]
+fn index_positions() {
+  let input = r##"{"a": [1, true], "b\"": "x"}"##;
+  let index = StructuralIndex::new(input);
+  let tokens: String = index.positions().iter().map(|&pos| input.as_bytes()[pos as usize] as char).collect();
+
+  assert_eq!(tokens, "{\"\":[1,t],\"\":\"\"}");
+}
+
+#[test]
+fn implementations_agree() {
+  // Move the document across block boundaries so that strings, escapes and
+  // literals get split between two blocks.
+  for padding in 0..130 {
+    let mut input = " ".repeat(padding);
+    input.push_str(&DOC.replace("\\\\", &"\\\\".repeat(padding % 7 + 1)));
+
+    let expected = StructuralIndex::with_implementation(&input, Implementation::Scalar);
+
+    for implementation in implementations() {
+      let index = StructuralIndex::with_implementation(&input, implementation);
+      assert_eq!(index.positions(), expected.positions(), "{:?}, padding {}", implementation, padding);
+    }
+  }
+}
+
+#[test]
+fn same_values_as_parser() {
+  assert_eq!(structural::parse(DOC).unwrap(), parse_json(DOC).unwrap());
+
+  let long = format!("[{}]", vec![DOC; 100].join(",\n"));
+  assert_eq!(structural::parse(&long).unwrap(), parse_json(&long).unwrap());
+
+  assert_eq!(structural::parse("42").unwrap(), JsonValue::Number(42.0));
+  assert_eq!(structural::parse("[1,]").unwrap(), parse_json("[1,]").unwrap());
+}
+
+#[test]
+fn invalid_documents() {
+  assert!(structural::parse("").is_err());
+  assert!(structural::parse("[\"abc").is_err());
+  assert!(structural::parse("[1 2]").is_err());
+  assert!(structural::parse("{\"a\" 1}").is_err());
+  assert!(structural::parse("[tru]").is_err());
+  assert!(structural::parse("[1.2.3]").is_err());
+  assert!(structural::parse("[3.]").is_err());
+  assert!(structural::parse("3.").is_err());
+  assert!(structural::parse("[\"\\x\"]").is_err());
+}
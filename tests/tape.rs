@@ -0,0 +1,79 @@
+extern crate json_parser;
+use json_parser::*;
+use json_parser::tape::*;
+
+const DOC: &'static str = r##"{
+  "name": "Short \"Huffman\"",
+  "age": 21,
+  "active": true,
+  "spouse": null,
+  "tags": ["a", "b", "c"],
+  "friends": [{"id": 0, "name": "Bridges"}, {"id": 1, "name": "Julie"}, {}],
+  "empty": []
+}"##;
+
+#[test]
+fn navigation() {
+  let tape = Tape::parse(DOC).unwrap();
+  let root = tape.root();
+
+  assert!(root.is_object());
+  assert_eq!(root.len(), Some(7));
+  assert_eq!(root.get("name").and_then(|v| v.as_str()), Some("Short \"Huffman\""));
+  assert_eq!(root.get("age").and_then(|v| v.as_f64()), Some(21.0));
+  assert_eq!(root.get("active").and_then(|v| v.as_bool()), Some(true));
+  assert!(root.get("spouse").unwrap().is_null());
+  assert!(root.get("missing").is_none());
+
+  let friends = root.get("friends").unwrap();
+  assert_eq!(friends.len(), Some(3));
+  assert_eq!(friends.at(1).and_then(|f| f.get("name")).and_then(|v| v.as_str()), Some("Julie"));
+  assert_eq!(friends.at(2).and_then(|f| f.len()), Some(0));
+  assert!(friends.at(3).is_none());
+
+  let tags: Vec<&str> = root.get("tags").unwrap().elements().map(|v| v.as_str().unwrap()).collect();
+  assert_eq!(tags, vec!["a", "b", "c"]);
+
+  let keys: Vec<&str> = root.members().map(|(key, _)| key).collect();
+  assert_eq!(keys, vec!["name", "age", "active", "spouse", "tags", "friends", "empty"]);
+
+  assert_eq!(root.get("empty").unwrap().elements().count(), 0);
+  assert_eq!(root.get("age").unwrap().elements().count(), 0);
+}
+
+#[test]
+fn duplicate_keys() {
+  let json = r#"{"a":1,"a":2}"#;
+  let tape = Tape::parse(json).unwrap();
+
+  assert_eq!(tape.root().get("a").and_then(|v| v.as_f64()), Some(2.0));
+  assert_eq!(tape.root().to_json_value(), parse_json(json).unwrap());
+}
+
+#[test]
+fn to_json_value() {
+  let tape = Tape::parse(DOC).unwrap();
+  assert_eq!(tape.root().to_json_value(), parse_json(DOC).unwrap());
+  assert_eq!(tape.root().get("friends").unwrap().to_json_value(),
+             *parse_json(DOC).unwrap().pointer("/friends").unwrap());
+
+  let scalar = Tape::parse("-1.5").unwrap();
+  assert_eq!(scalar.root().to_json_value(), JsonValue::Number(-1.5));
+}
+
+#[test]
+fn compact() {
+  let input = format!("[{}]", vec![r#"{"id": 1, "tags": ["x", "y"], "ok": true}"#; 1000].join(","));
+  let tape = Tape::parse(&input).unwrap();
+
+  // Each object takes 17 entries (2 per string and number, 1 per bracket
+  // and literal) and 10 bytes of strings.
+  assert_eq!(tape.heap_size(), 2 * 8 + 1000 * (17 * 8 + 10));
+}
+
+#[test]
+fn invalid_documents() {
+  assert!(Tape::parse("[1, 2").is_err());
+  assert!(Tape::parse("{\"a\": }").is_err());
+  assert!(Tape::parse("{1: 2}").is_err());
+}
@@ -13,3 +13,39 @@ fn hebrew_russian_ascii() {
 
   assert_eq!(parse_json(input).unwrap(), expected);
 }
+
+#[test]
+fn error_columns() {
+  let input = r##"{"привет world":"שלום привет hello" x}"##;
+  let error = parse_json(input).unwrap_err();
+
+  assert_eq!(error.line(), 1);
+  assert_eq!(error.column(), 37);
+  assert_eq!(error.byte_column(), input.find(" x").unwrap() + 2);
+  assert_eq!(error.utf16_column(), 37);
+
+  let input = "{\n  \"😀 emoji\": [\"𝄞\", \"é\"\n  \"ü\": 1}";
+  let error = parse_json(input).unwrap_err();
+
+  assert_eq!(error.line(), 3);
+  assert_eq!(error.offset(), input.find("\"ü").unwrap());
+  assert_eq!((error.column(), error.byte_column(), error.utf16_column()), (3, 3, 3));
+}
+
+#[test]
+fn error_columns_after_multibyte() {
+  let input = "[\"😀 𝄞 é\", tru]";
+  let error = parse_json(input).unwrap_err();
+
+  assert_eq!(error.offset(), 20);
+  assert_eq!(error.column(), 14);
+  assert_eq!(error.byte_column(), 21);
+  assert_eq!(error.utf16_column(), 16);
+
+  // A limit exceeded in the middle of a character points at it.
+  let options = ParserOptions { max_string_len: Some(2), ..ParserOptions::default() };
+  let error = JsonParser::with_options("[\"😀\"]", options).parse().unwrap_err();
+
+  assert_eq!(error.offset(), 4);
+  assert_eq!((error.column(), error.byte_column(), error.utf16_column()), (3, 5, 3));
+}